@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{solution::DynSolution, util::read_input};
+
+pub const ANSWERS_FILE: &str = "answers.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Answer {
+    pub day: usize,
+    pub part: usize,
+    pub input: String, // name of the input file in the day dir
+    pub answer: String,
+}
+
+#[derive(Debug)]
+pub struct AnswerStore {
+    path: PathBuf,
+    answers: Vec<Answer>, // sorted by day, part and input
+}
+
+impl AnswerStore {
+    // ./answers.json if it exists, answers.json of this crate checkout otherwise
+    pub fn default_path() -> PathBuf {
+        let local = PathBuf::from(ANSWERS_FILE);
+        if local.exists() {
+            local
+        } else {
+            Path::new(env!("CARGO_MANIFEST_DIR")).join(ANSWERS_FILE)
+        }
+    }
+
+    // a missing file is an empty store, it will be created by save
+    pub fn load(path: &Path) -> Result<AnswerStore> {
+        let mut answers: Vec<Answer> = match fs::read_to_string(path) {
+            Ok(s) => serde_json::from_str(&s).with_context(|| anyhow!("parsing {:?}", path))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err).with_context(|| anyhow!("reading {:?}", path)),
+        };
+        answers.sort_by(|a, b| Self::key(a).cmp(&Self::key(b)));
+        Ok(AnswerStore {
+            path: path.to_path_buf(),
+            answers,
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut s = serde_json::to_string_pretty(&self.answers)?;
+        s.push('\n');
+        fs::write(&self.path, s).with_context(|| anyhow!("writing {:?}", self.path))
+    }
+
+    fn key(answer: &Answer) -> (usize, usize, &str) {
+        (answer.day, answer.part, answer.input.as_str())
+    }
+
+    pub fn for_solution(&self, day: usize, part: usize) -> impl Iterator<Item = &Answer> {
+        self.answers
+            .iter()
+            .filter(move |it| it.day == day && it.part == part)
+    }
+
+    // adds the answer or replaces the previously stored one
+    pub fn record(&mut self, answer: Answer) {
+        match self
+            .answers
+            .binary_search_by(|it| Self::key(it).cmp(&Self::key(&answer)))
+        {
+            Ok(pos) => self.answers[pos] = answer,
+            Err(pos) => self.answers.insert(pos, answer),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    Error(anyhow::Error),
+    Missing,
+    Recorded(String),
+    Updated { previous: String, answer: String },
+}
+
+// checks the solution against every stored answer for it, with `record` the answer for
+// input.txt is captured if there is none yet, `update` also replaces the ones which do not match
+pub fn verify(
+    solution: &dyn DynSolution,
+    store: &mut AnswerStore,
+    record: bool,
+    update: bool,
+) -> Vec<(String, Verdict)> {
+    let (day, part) = (solution.day(), solution.part());
+    let run = |input: &str| read_input(day, input).and_then(|s| solution.run_on(&s));
+
+    let mut verdicts = store
+        .for_solution(day, part)
+        .map(|expected| {
+            let verdict = match run(&expected.input) {
+                Ok(actual) if actual == expected.answer => Verdict::Pass,
+                Ok(actual) if update => Verdict::Updated {
+                    previous: expected.answer.clone(),
+                    answer: actual,
+                },
+                Ok(actual) => Verdict::Fail {
+                    expected: expected.answer.clone(),
+                    actual,
+                },
+                Err(err) => Verdict::Error(err),
+            };
+            (expected.input.clone(), verdict)
+        })
+        .collect::<Vec<_>>();
+    for (input, verdict) in verdicts.iter() {
+        if let Verdict::Updated { answer, .. } = verdict {
+            store.record(Answer {
+                day,
+                part,
+                input: input.clone(),
+                answer: answer.clone(),
+            });
+        }
+    }
+
+    if verdicts.is_empty() {
+        let input = "input.txt".to_string();
+        let verdict = if record || update {
+            match run(&input) {
+                Ok(answer) => {
+                    store.record(Answer {
+                        day,
+                        part,
+                        input: input.clone(),
+                        answer: answer.clone(),
+                    });
+                    Verdict::Recorded(answer)
+                }
+                Err(err) => Verdict::Error(err),
+            }
+        } else {
+            Verdict::Missing
+        };
+        verdicts.push((input, verdict));
+    }
+
+    verdicts
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::registry::REGISTRY;
+
+    fn answer(day: usize, part: usize, input: &str, answer: &str) -> Answer {
+        Answer {
+            day,
+            part,
+            input: input.to_string(),
+            answer: answer.to_string(),
+        }
+    }
+
+    impl AnswerStore {
+        fn expected(&self, day: usize, part: usize, input: &str) -> Option<&str> {
+            self.for_solution(day, part)
+                .find(|it| it.input == input)
+                .map(|it| it.answer.as_str())
+        }
+    }
+
+    fn empty_store() -> AnswerStore {
+        AnswerStore {
+            path: PathBuf::from("no_such_dir/answers.json"),
+            answers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut store = empty_store();
+        store.record(answer(2, 1, "input.txt", "1"));
+        store.record(answer(1, 2, "input.txt", "2"));
+        store.record(answer(1, 2, "test.txt", "3"));
+        store.record(answer(1, 2, "input.txt", "4"));
+
+        assert_eq!(Some("4"), store.expected(1, 2, "input.txt"));
+        assert_eq!(Some("3"), store.expected(1, 2, "test.txt"));
+        assert_eq!(Some("1"), store.expected(2, 1, "input.txt"));
+        assert_eq!(None, store.expected(1, 1, "input.txt"));
+        assert_eq!(2, store.for_solution(1, 2).count());
+    }
+
+    #[test]
+    fn test_verify() {
+        let solution = REGISTRY.find(1, 1).unwrap();
+        let mut store = empty_store();
+
+        let verdicts = verify(solution, &mut store, false, false);
+        assert!(matches!(verdicts[..], [(_, Verdict::Missing)]));
+
+        store.record(answer(1, 1, "test.txt", "24000"));
+        store.record(answer(1, 1, "no_such_input.txt", "24000"));
+        store.record(answer(1, 1, "input.txt", "1"));
+        let verdicts = verify(solution, &mut store, false, false);
+        assert!(matches!(
+            verdicts[..],
+            [
+                (_, Verdict::Fail { .. }),
+                (_, Verdict::Error(_)),
+                (_, Verdict::Pass)
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_verify_record() {
+        let solution = REGISTRY.find(1, 2).unwrap();
+        let mut store = empty_store();
+
+        let verdicts = verify(solution, &mut store, true, false);
+        assert!(matches!(&verdicts[..], [(_, Verdict::Recorded(answer))] if answer == "209914"));
+        assert_eq!(Some("209914"), store.expected(1, 2, "input.txt"));
+        // updating records the missing answers as well
+        let mut other_store = empty_store();
+        let verdicts = verify(solution, &mut other_store, false, true);
+        assert!(matches!(&verdicts[..], [(_, Verdict::Recorded(answer))] if answer == "209914"));
+
+        let verdicts = verify(solution, &mut store, true, false);
+        assert!(matches!(verdicts[..], [(_, Verdict::Pass)]));
+        // a changed answer is a failure, recording or not, unless it is updated
+        store.record(answer(1, 2, "input.txt", "1"));
+        store.record(answer(1, 2, "test.txt", "45000"));
+        for record in [false, true] {
+            let verdicts = verify(solution, &mut store, record, false);
+            assert!(matches!(
+                verdicts[..],
+                [(_, Verdict::Fail { .. }), (_, Verdict::Pass)]
+            ));
+            assert_eq!(Some("1"), store.expected(1, 2, "input.txt"));
+        }
+
+        let verdicts = verify(solution, &mut store, false, true);
+        assert!(matches!(
+            &verdicts[..],
+            [(_, Verdict::Updated { previous, answer }), (_, Verdict::Pass)]
+                if previous == "1" && answer == "209914"
+        ));
+        assert_eq!(Some("209914"), store.expected(1, 2, "input.txt"));
+        assert!(matches!(
+            verify(solution, &mut store, false, true)[..],
+            [(_, Verdict::Pass), (_, Verdict::Pass)]
+        ));
+    }
+
+    #[test]
+    fn test_stored_answers_parse() -> Result<()> {
+        let store = AnswerStore::load(&AnswerStore::default_path())?;
+        assert!(store.expected(1, 1, "input.txt").is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{ensure, Result};
+use std::time::Duration;
+
+use crate::solution::DynSolution;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        Some(Stats {
+            min: *sorted.first()?,
+            median: sorted[(sorted.len() - 1) / 2],
+            max: *sorted.last()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub day: usize,
+    pub part: usize,
+    pub runs: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+pub fn bench(solution: &dyn DynSolution, input_str: &str, runs: usize) -> Result<BenchResult> {
+    ensure!(runs > 0, "at least one run is needed");
+
+    let mut answer = None;
+    let mut parse_times = Vec::with_capacity(runs);
+    let mut solve_times = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let run = solution.run_timed(input_str)?;
+        if let Some(prev_answer) = &answer {
+            ensure!(
+                *prev_answer == run.answer,
+                "answer changed between runs: {:?} != {:?}",
+                prev_answer,
+                run.answer
+            );
+        }
+        answer = Some(run.answer);
+        parse_times.push(run.parse_time);
+        solve_times.push(run.solve_time);
+    }
+
+    Ok(BenchResult {
+        day: solution.day(),
+        part: solution.part(),
+        runs,
+        parse: Stats::from_samples(&parse_times).unwrap(),
+        solve: Stats::from_samples(&solve_times).unwrap(),
+    })
+}
+
+fn format_stats(stats: &Stats) -> String {
+    format!(
+        "{:.2?} / {:.2?} / {:.2?}",
+        stats.min, stats.median, stats.max
+    )
+}
+
+pub fn format_result(result: &BenchResult) -> String {
+    format!(
+        "Day {} Part {}: parse {}, solve {} (min / median / max of {} runs)",
+        result.day,
+        result.part,
+        format_stats(&result.parse),
+        format_stats(&result.solve),
+        result.runs
+    )
+}
+
+pub fn format_table(results: &[BenchResult]) -> String {
+    let header = [
+        "day",
+        "part",
+        "parse min",
+        "parse median",
+        "parse max",
+        "solve min",
+        "solve median",
+        "solve max",
+    ];
+    let rows = results
+        .iter()
+        .map(|it| {
+            let parse = &it.parse;
+            let solve = &it.solve;
+            vec![
+                it.day.to_string(),
+                it.part.to_string(),
+                format!("{:.2?}", parse.min),
+                format!("{:.2?}", parse.median),
+                format!("{:.2?}", parse.max),
+                format!("{:.2?}", solve.min),
+                format!("{:.2?}", solve.median),
+                format!("{:.2?}", solve.max),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let widths = (0..header.len())
+        .map(|col| {
+            rows.iter()
+                .map(|row| row[col].len())
+                .chain([header[col].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let mut write_row = |row: &[&str]| {
+        let cells = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, &width)| format!("{:>width$}", cell, width = width))
+            .collect::<Vec<_>>();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    };
+    write_row(&header);
+    for row in rows.iter() {
+        write_row(&row.iter().map(|it| it.as_str()).collect::<Vec<_>>());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{registry::REGISTRY, util::read_input};
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(None, Stats::from_samples(&[]));
+        assert_eq!(
+            Some(Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(7)
+            }),
+            Stats::from_samples(&[ms(7), ms(1), ms(3), ms(5), ms(2)])
+        );
+        assert_eq!(
+            Some(Stats {
+                min: ms(2),
+                median: ms(2),
+                max: ms(4)
+            }),
+            Stats::from_samples(&[ms(4), ms(2)])
+        );
+    }
+
+    #[test]
+    fn test_bench() -> Result<()> {
+        let solution = REGISTRY.find(1, 1).unwrap();
+        let input_str = read_input(1, "test.txt")?;
+        let result = bench(solution, &input_str, 3)?;
+        assert_eq!(3, result.runs);
+        assert!(result.parse.min <= result.parse.median);
+        assert!(result.solve.median <= result.solve.max);
+
+        assert!(bench(solution, &input_str, 0).is_err());
+        assert!(bench(solution, "foo", 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_table() {
+        let stats = Stats {
+            min: ms(1),
+            median: ms(2),
+            max: ms(30),
+        };
+        let result = BenchResult {
+            day: 15,
+            part: 2,
+            runs: 1,
+            parse: stats,
+            solve: stats,
+        };
+        let table = format_table(&[result]);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].starts_with("day  part  parse min"));
+        assert!(lines[1].starts_with(" 15     2     1.00ms"));
+        assert!(lines[1].ends_with("30.00ms"));
+    }
+}
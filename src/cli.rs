@@ -0,0 +1,791 @@
+use anyhow::{anyhow, bail, ensure, Context, Error, Result};
+use std::{ops::RangeInclusive, path::PathBuf, str::FromStr};
+
+use crate::{
+    answers::ANSWERS_FILE,
+    day15::FREE_SEARCH_VAR,
+    input::{InputSource, INPUT_DIR_VAR},
+};
+
+pub fn usage() -> String {
+    format!(
+        "\
+Usage:
+    aoc2022 [run] [DAYS] [--part N] [--input FILE] [--format F]
+                                                      run solutions, all of them by default
+    aoc2022 bench [DAYS] [--part N] [--input FILE] [--format F] [--runs N] [--table]
+                                                      measure parse and solve time of solutions
+    aoc2022 verify [DAYS] [--part N] [--record] [--update]
+                                                      check solutions against stored answers
+    aoc2022 list [DAYS] [--part N]                    list available solutions
+    aoc2022 dot 16 [--part N] [--compressed] [--route] [--input FILE]
+                                                      print the tunnels of day 16 as a graphviz graph
+    aoc2022 map 15 [--view X1,Y1,X2,Y2] [--sensor X,Y] [--image FILE] [--size WxH] [--input FILE]
+                                                      draw the sensor areas of day 15
+    aoc2022 map 12 [--routes] [--input FILE]          draw the shortest route of day 12
+    aoc2022 trace 10 [--cycles N] [--break PC]... [--csv] [--input FILE]
+                                                      show the registers of day 10 at every cycle
+    aoc2022 help                                      show this message
+
+DAYS is a comma separated list of days or day ranges, e.g. 15, 1..=10, ..=5, 1..5,7,9..
+FILE is used as the input of every selected solution, - reads it from stdin.
+F is text (default) or json, json prints one object per solution and line.
+Without --input every day reads day<N>/input.txt from ${} if set, then from ./src.
+${} picks the search of day 15 part 2: lines (the default) or perimeter.
+bench repeats every solution --runs times ({} by default), --table adds a summary of all of them.
+verify reads the answers from {}, --record stores the answers for input.txt that are missing there,
+--update also replaces the stored answers that do not match.
+dot draws every tunnel, --compressed only the valves with the travel times between them,
+--route colors the way the agents of the part (1 by default) go.
+map 12 draws the route from the start, --routes the shortest ones from the lowest points instead.
+map 15 prints the part of the field from X1,Y1 to X2,Y2, all the sensors and beacons by default,
+--sensor draws only the area of the sensor at X,Y. --image writes a FILE.pgm or, in color,
+a FILE.ppm scaled down to --size pixels ({}x{} by default) instead.
+trace runs the program for --cycles cycles ({} by default) or until it ends, and stops before
+the instruction at index PC runs, if given; --csv prints the trace as CSV.
+",
+        INPUT_DIR_VAR,
+        FREE_SEARCH_VAR,
+        DEFAULT_BENCH_RUNS,
+        ANSWERS_FILE,
+        DEFAULT_IMAGE_SIZE.0,
+        DEFAULT_IMAGE_SIZE.1,
+        DEFAULT_TRACE_CYCLES
+    )
+}
+
+pub const DEFAULT_BENCH_RUNS: usize = 5;
+pub const DEFAULT_IMAGE_SIZE: (usize, usize) = (400, 400);
+pub const DEFAULT_TRACE_CYCLES: usize = 240; // all the pixels of the CRT
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<OutputFormat> {
+        Ok(match s {
+            "text" => OutputFormat::Text,
+            "json" => OutputFormat::Json,
+            _ => bail!("unexpected format {:?}, expected text or json", s),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RunArgs {
+    pub filter: Filter,
+    pub input: InputSource,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchArgs {
+    pub run_args: RunArgs,
+    pub runs: usize,
+    pub table: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyArgs {
+    pub filter: Filter,
+    pub record: bool, // store the missing answers
+    pub update: bool, // and replace the ones that do not match
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotArgs {
+    pub input: InputSource,
+    pub part: usize, // the part whose route is drawn
+    pub compressed: bool,
+    pub route: bool,
+}
+
+pub type MapPoint = (i64, i64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapArgs {
+    pub day: usize,   // 12 or 15
+    pub routes: bool, // day 12, from the lowest points instead of the start
+    pub input: InputSource,
+    pub view: Option<(MapPoint, MapPoint)>, // None is around all the sensors and beacons
+    pub sensor: Option<MapPoint>,           // the only one whose area is drawn
+    pub image: Option<PathBuf>,             // .pgm or .ppm, instead of the text map
+    pub size: (usize, usize),
+}
+
+impl MapArgs {
+    pub fn color(&self) -> bool {
+        self.image
+            .as_ref()
+            .is_some_and(|path| path.extension().is_some_and(|ext| ext == "ppm"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceArgs {
+    pub input: InputSource,
+    pub cycles: usize,
+    pub breakpoints: Vec<usize>, // instruction indices
+    pub csv: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run(RunArgs),
+    Bench(BenchArgs),
+    Verify(VerifyArgs),
+    List(Filter),
+    Dot(DotArgs),
+    Map(MapArgs),
+    Trace(TraceArgs),
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Filter {
+    days: Vec<RangeInclusive<usize>>, // empty means every day
+    part: Option<usize>,
+}
+
+impl Filter {
+    pub fn matches(&self, day: usize, part: usize) -> bool {
+        let day_matches = self.days.is_empty() || self.days.iter().any(|it| it.contains(&day));
+        let part_matches = self.part.map(|it| it == part).unwrap_or(true);
+        day_matches && part_matches
+    }
+}
+
+fn parse_num(s: &str) -> Result<usize> {
+    s.parse().with_context(|| anyhow!("bad number {:?}", s))
+}
+
+// N, A..B or A..=B; A defaults to 1, and in A..B only, B to the last day
+fn parse_day_range(s: &str) -> Result<RangeInclusive<usize>> {
+    let parse_start = |start: &str| {
+        if start.is_empty() {
+            Ok(1)
+        } else {
+            parse_num(start)
+        }
+    };
+    let range = if let Some((start, end)) = s.split_once("..=") {
+        parse_start(start)?..=parse_num(end)?
+    } else if let Some((start, end)) = s.split_once("..") {
+        let end = if end.is_empty() {
+            usize::MAX
+        } else {
+            parse_num(end)?
+                .checked_sub(1)
+                .ok_or_else(|| anyhow!("empty range {:?}", s))?
+        };
+        parse_start(start)?..=end
+    } else {
+        let day = parse_num(s)?;
+        day..=day
+    };
+    ensure!(!range.is_empty(), "empty range {:?}", s);
+    Ok(range)
+}
+
+fn parse_coords(s: &str, count: usize) -> Result<Vec<i64>> {
+    let coords = s
+        .split(',')
+        .map(|it| {
+            it.trim()
+                .parse()
+                .with_context(|| anyhow!("bad number {:?}", it))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    ensure!(
+        coords.len() == count,
+        "expected {} numbers separated by ',', got {:?}",
+        count,
+        s
+    );
+    Ok(coords)
+}
+
+fn parse_point(s: &str) -> Result<MapPoint> {
+    let coords = parse_coords(s, 2)?;
+    Ok((coords[0], coords[1]))
+}
+
+fn parse_view(s: &str) -> Result<(MapPoint, MapPoint)> {
+    let coords = parse_coords(s, 4)?;
+    Ok(((coords[0], coords[1]), (coords[2], coords[3])))
+}
+
+fn parse_size(s: &str) -> Result<(usize, usize)> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| anyhow!("expected WIDTHxHEIGHT, got {:?}", s))?;
+    Ok((parse_num(width)?, parse_num(height)?))
+}
+
+pub fn parse_days(s: &str) -> Result<Vec<RangeInclusive<usize>>> {
+    s.split(',')
+        .map(|it| parse_day_range(it.trim()).with_context(|| anyhow!("parsing days {:?}", s)))
+        .collect()
+}
+
+// options of all commands, every command checks that it gets only the ones it supports
+#[derive(Debug, Default)]
+struct Options {
+    run_args: RunArgs,
+    runs: Option<usize>,
+    table: bool,
+    record: bool,
+    update: bool,
+    compressed: bool,
+    route: bool,
+    view: Option<(MapPoint, MapPoint)>,
+    sensor: Option<MapPoint>,
+    image: Option<PathBuf>,
+    size: Option<(usize, usize)>,
+    cycles: Option<usize>,
+    breakpoints: Vec<usize>,
+    csv: bool,
+    routes: bool,
+}
+
+impl Options {
+    fn check_no_drawing_options(&self, command: &str) -> Result<()> {
+        ensure!(
+            !self.compressed && !self.route,
+            "--compressed and --route are only supported by dot, not by {}",
+            command
+        );
+        ensure!(
+            self.view.is_none()
+                && self.sensor.is_none()
+                && self.image.is_none()
+                && self.size.is_none()
+                && !self.routes,
+            "--view, --sensor, --image, --size and --routes are only supported by map, not by {}",
+            command
+        );
+        Ok(())
+    }
+
+    fn into_run_args(self, command: &str) -> Result<RunArgs> {
+        self.check_no_drawing_options(command)?;
+        ensure!(
+            self.runs.is_none() && !self.table,
+            "--runs and --table are only supported by bench, not by {}",
+            command
+        );
+        ensure!(
+            !self.record && !self.update,
+            "--record and --update are only supported by verify, not by {}",
+            command
+        );
+        ensure!(
+            self.cycles.is_none() && self.breakpoints.is_empty() && !self.csv,
+            "--cycles, --break and --csv are only supported by trace, not by {}",
+            command
+        );
+        Ok(self.run_args)
+    }
+
+    fn into_filter(self, command: &str) -> Result<Filter> {
+        let run_args = self.into_run_args(command)?;
+        ensure!(
+            run_args.input == InputSource::Default,
+            "--input is not supported by {}",
+            command
+        );
+        ensure!(
+            run_args.format == OutputFormat::Text,
+            "--format is not supported by {}",
+            command
+        );
+        Ok(run_args.filter)
+    }
+
+    fn into_verify_args(mut self) -> Result<VerifyArgs> {
+        let record = std::mem::take(&mut self.record);
+        let update = std::mem::take(&mut self.update);
+        Ok(VerifyArgs {
+            filter: self.into_filter("verify")?,
+            record,
+            update,
+        })
+    }
+
+    fn into_bench_args(self) -> Result<BenchArgs> {
+        ensure!(
+            !self.record && !self.update,
+            "--record and --update are only supported by verify"
+        );
+        ensure!(
+            self.cycles.is_none() && self.breakpoints.is_empty() && !self.csv,
+            "--cycles, --break and --csv are only supported by trace"
+        );
+        self.check_no_drawing_options("bench")?;
+        let runs = self.runs.unwrap_or(DEFAULT_BENCH_RUNS);
+        ensure!(runs > 0, "--runs must be positive");
+        Ok(BenchArgs {
+            run_args: self.run_args,
+            runs,
+            table: self.table,
+        })
+    }
+
+    fn into_dot_args(mut self) -> Result<DotArgs> {
+        let compressed = std::mem::take(&mut self.compressed);
+        let route = std::mem::take(&mut self.route);
+        let run_args = self.into_run_args("dot")?;
+        ensure!(
+            run_args.filter.days == [16..=16],
+            "dot is only supported by day 16"
+        );
+        ensure!(
+            run_args.format == OutputFormat::Text,
+            "--format is not supported by dot"
+        );
+        Ok(DotArgs {
+            input: run_args.input,
+            part: run_args.filter.part.unwrap_or(1),
+            compressed,
+            route,
+        })
+    }
+
+    fn into_map_args(mut self) -> Result<MapArgs> {
+        let view = self.view.take();
+        let sensor = self.sensor.take();
+        let image = self.image.take();
+        let size = self.size.take();
+        let routes = std::mem::take(&mut self.routes);
+        let run_args = self.into_run_args("map")?;
+        let day = match &run_args.filter.days[..] {
+            [days] if days == &(12..=12) || days == &(15..=15) => *days.start(),
+            _ => bail!("map is only supported by days 12 and 15"),
+        };
+        ensure!(
+            run_args.filter.part.is_none(),
+            "--part is not supported by map"
+        );
+        ensure!(
+            run_args.format == OutputFormat::Text,
+            "--format is not supported by map"
+        );
+        if day == 12 {
+            ensure!(
+                view.is_none() && sensor.is_none() && image.is_none() && size.is_none(),
+                "--view, --sensor, --image and --size are only supported by map 15"
+            );
+        } else {
+            ensure!(!routes, "--routes is only supported by map 12");
+        }
+        match &image {
+            Some(path) => {
+                ensure!(
+                    path.extension()
+                        .is_some_and(|ext| ext == "pgm" || ext == "ppm"),
+                    "--image must be a .pgm or a .ppm file, not {:?}",
+                    path
+                );
+                ensure!(sensor.is_none(), "--sensor is not supported with --image");
+            }
+            None => ensure!(size.is_none(), "--size is only supported with --image"),
+        }
+        Ok(MapArgs {
+            day,
+            routes,
+            input: run_args.input,
+            view,
+            sensor,
+            image,
+            size: size.unwrap_or(DEFAULT_IMAGE_SIZE),
+        })
+    }
+
+    fn into_trace_args(mut self) -> Result<TraceArgs> {
+        let cycles = self.cycles.take().unwrap_or(DEFAULT_TRACE_CYCLES);
+        let breakpoints = std::mem::take(&mut self.breakpoints);
+        let csv = std::mem::take(&mut self.csv);
+        let run_args = self.into_run_args("trace")?;
+        ensure!(
+            run_args.filter.days == [10..=10] && run_args.filter.part.is_none(),
+            "trace is only supported by day 10, without --part"
+        );
+        ensure!(
+            run_args.format == OutputFormat::Text,
+            "--format is not supported by trace, use --csv"
+        );
+        ensure!(cycles > 0, "--cycles must be positive");
+        Ok(TraceArgs {
+            input: run_args.input,
+            cycles,
+            breakpoints,
+            csv,
+        })
+    }
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options> {
+    let mut options = Options::default();
+    let run_args = &mut options.run_args;
+    let filter = &mut run_args.filter;
+    while let Some(arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| anyhow!("{} requires a value", name))
+        };
+        match name {
+            "-p" | "--part" => filter.part = Some(parse_num(&value()?)?),
+            "-i" | "--input" => run_args.input = InputSource::from_arg(&value()?),
+            "-f" | "--format" => run_args.format = value()?.parse()?,
+            "-n" | "--runs" => options.runs = Some(parse_num(&value()?)?),
+            "--table" => options.table = true,
+            "--record" => options.record = true,
+            "--update" => options.update = true,
+            "--compressed" => options.compressed = true,
+            "--route" => options.route = true,
+            "--routes" => options.routes = true,
+            "--view" => options.view = Some(parse_view(&value()?)?),
+            "--sensor" => options.sensor = Some(parse_point(&value()?)?),
+            "--image" => options.image = Some(PathBuf::from(value()?)),
+            "--size" => options.size = Some(parse_size(&value()?)?),
+            "--cycles" => options.cycles = Some(parse_num(&value()?)?),
+            "--break" => options.breakpoints.push(parse_num(&value()?)?),
+            "--csv" => options.csv = true,
+            "-" => bail!("unexpected argument \"-\", did you mean --input -?"),
+            _ if name.starts_with('-') => bail!("unexpected option {:?}", arg),
+            _ => {
+                ensure!(filter.days.is_empty(), "days specified twice: {:?}", arg);
+                filter.days = parse_days(&arg)?;
+            }
+        }
+    }
+    Ok(options)
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command> {
+    let mut args = args.into_iter().peekable();
+    let command = match args.peek().map(|it| it.as_str()) {
+        Some("help" | "-h" | "--help") => Command::Help,
+        Some("list") => {
+            args.next();
+            Command::List(parse_options(args)?.into_filter("list")?)
+        }
+        Some("verify") => {
+            args.next();
+            Command::Verify(parse_options(args)?.into_verify_args()?)
+        }
+        Some("bench") => {
+            args.next();
+            Command::Bench(parse_options(args)?.into_bench_args()?)
+        }
+        Some("dot") => {
+            args.next();
+            Command::Dot(parse_options(args)?.into_dot_args()?)
+        }
+        Some("map") => {
+            args.next();
+            Command::Map(parse_options(args)?.into_map_args()?)
+        }
+        Some("trace") => {
+            args.next();
+            Command::Trace(parse_options(args)?.into_trace_args()?)
+        }
+        Some("run") => {
+            args.next();
+            Command::Run(parse_options(args)?.into_run_args("run")?)
+        }
+        _ => Command::Run(parse_options(args)?.into_run_args("run")?),
+    };
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn parse(s: &str) -> Result<Command> {
+        parse_args(s.split_whitespace().map(|it| it.to_string()))
+    }
+
+    #[test]
+    fn test_parse_days() -> Result<()> {
+        assert_eq!(vec![15..=15], parse_days("15")?);
+        assert_eq!(vec![1..=10], parse_days("1..=10")?);
+        assert_eq!(vec![1..=9], parse_days("1..10")?);
+        assert_eq!(vec![1..=4, 7..=7, 9..=usize::MAX], parse_days("..5,7,9..")?);
+        assert_eq!(vec![3..=5], parse_days("3..=5")?);
+        assert_eq!(vec![3..=4], parse_days("3..5")?);
+        assert_eq!(vec![1..=5], parse_days("..=5")?);
+        assert_eq!(vec![1..=4], parse_days("..5")?);
+        assert_eq!(vec![3..=usize::MAX], parse_days("3..")?);
+        assert_eq!(vec![1..=usize::MAX], parse_days("..")?);
+        assert_eq!(vec![5..=5], parse_days("5..=5")?);
+        assert!(parse_days("5..5").is_err());
+        assert!(parse_days("..1").is_err());
+        assert!(parse_days("10..=1").is_err());
+        assert!(parse_days("3..=").is_err());
+        assert!(parse_days("3-5").is_err());
+        assert!(parse_days("foo").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args() -> Result<()> {
+        assert_eq!(Command::Run(RunArgs::default()), parse("")?);
+        assert_eq!(Command::Help, parse("--help")?);
+        assert_eq!(
+            Command::Run(RunArgs {
+                filter: Filter {
+                    days: vec![15..=15],
+                    part: Some(2)
+                },
+                input: InputSource::Default,
+                format: OutputFormat::Text
+            }),
+            parse("run 15 --part 2")?
+        );
+        assert_eq!(
+            Command::List(Filter {
+                days: vec![1..=10],
+                part: None
+            }),
+            parse("list 1..=10")?
+        );
+        assert_eq!(
+            Command::Run(RunArgs {
+                filter: Filter {
+                    days: vec![],
+                    part: Some(1)
+                },
+                input: InputSource::Stdin,
+                format: OutputFormat::Json
+            }),
+            parse("--part=1 --input - --format json")?
+        );
+        assert_eq!(
+            Command::Run(RunArgs {
+                filter: Filter {
+                    days: vec![15..=15],
+                    part: None
+                },
+                input: InputSource::File("my.txt".into()),
+                format: OutputFormat::Text
+            }),
+            parse("15 --input=my.txt")?
+        );
+        assert!(parse("list 15 --input my.txt").is_err());
+        assert!(parse("run 15 --runs 3").is_err());
+        assert!(parse("list --table").is_err());
+        assert!(parse("run 1 2").is_err());
+        assert!(parse("run --part").is_err());
+        assert!(parse("run --foo").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bench_args() -> Result<()> {
+        assert_eq!(
+            Command::Bench(BenchArgs {
+                run_args: RunArgs::default(),
+                runs: DEFAULT_BENCH_RUNS,
+                table: false
+            }),
+            parse("bench")?
+        );
+        assert_eq!(
+            Command::Bench(BenchArgs {
+                run_args: RunArgs {
+                    filter: Filter {
+                        days: vec![16..=16],
+                        part: None
+                    },
+                    input: InputSource::Default,
+                    format: OutputFormat::Text
+                },
+                runs: 20,
+                table: true
+            }),
+            parse("bench 16 --table --runs=20")?
+        );
+        assert!(parse("bench -n 0").is_err());
+        assert!(parse("bench --record").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_verify_args() -> Result<()> {
+        assert_eq!(
+            Command::Verify(VerifyArgs {
+                filter: Filter {
+                    days: vec![1..=1],
+                    part: Some(2)
+                },
+                record: true,
+                update: false
+            }),
+            parse("verify 1 -p 2 --record")?
+        );
+        assert_eq!(
+            Command::Verify(VerifyArgs {
+                filter: Filter::default(),
+                record: false,
+                update: true
+            }),
+            parse("verify --update")?
+        );
+        assert!(parse("run --update").is_err());
+        assert!(parse("verify --input my.txt").is_err());
+        assert!(parse("run --record").is_err());
+        assert!(parse("verify --format json").is_err());
+        assert!(parse("run --format xml").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_dot_args() -> Result<()> {
+        assert_eq!(
+            Command::Dot(DotArgs {
+                input: InputSource::Default,
+                part: 1,
+                compressed: false,
+                route: false
+            }),
+            parse("dot 16")?
+        );
+        assert_eq!(
+            Command::Dot(DotArgs {
+                input: InputSource::File("my.txt".into()),
+                part: 2,
+                compressed: true,
+                route: true
+            }),
+            parse("dot 16 --route --part 2 --compressed --input my.txt")?
+        );
+        assert!(parse("dot").is_err());
+        assert!(parse("dot 15").is_err());
+        assert!(parse("dot 15,16").is_err());
+        assert!(parse("dot 16 --record").is_err());
+        assert!(parse("dot 16 --format json").is_err());
+        assert!(parse("run 16 --route").is_err());
+        assert!(parse("bench 16 --compressed").is_err());
+        assert!(parse("verify 16 --compressed").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_map_args() -> Result<()> {
+        assert_eq!(
+            Command::Map(MapArgs {
+                day: 15,
+                routes: false,
+                input: InputSource::File("test.txt".into()),
+                view: Some(((-2, -2), (25, 16))),
+                sensor: Some((8, 7)),
+                image: None,
+                size: DEFAULT_IMAGE_SIZE
+            }),
+            parse("map 15 --input test.txt --view -2,-2,25,16 --sensor 8,7")?
+        );
+        let args = MapArgs {
+            day: 15,
+            routes: false,
+            input: InputSource::Default,
+            view: None,
+            sensor: None,
+            image: Some("field.ppm".into()),
+            size: (200, 100),
+        };
+        assert!(args.color());
+        assert_eq!(
+            Command::Map(args),
+            parse("map 15 --image field.ppm --size 200x100")?
+        );
+        assert_eq!(
+            Command::Map(MapArgs {
+                day: 12,
+                routes: true,
+                input: InputSource::File("test.txt".into()),
+                view: None,
+                sensor: None,
+                image: None,
+                size: DEFAULT_IMAGE_SIZE
+            }),
+            parse("map 12 --routes --input test.txt")?
+        );
+        assert!(parse("map 16").is_err());
+        assert!(parse("map 12,15").is_err());
+        assert!(parse("map 12 --view 1,2,3,4").is_err());
+        assert!(parse("map 12 --image field.pgm").is_err());
+        assert!(parse("map 15 --routes").is_err());
+        assert!(parse("run 12 --routes").is_err());
+        assert!(parse("map 15 --part 1").is_err());
+        assert!(parse("map 15 --image field.png").is_err());
+        assert!(parse("map 15 --image field.pgm --sensor 8,7").is_err());
+        assert!(parse("map 15 --size 200x100").is_err());
+        assert!(parse("map 15 --size 200").is_err());
+        assert!(parse("map 15 --view 1,2,3").is_err());
+        assert!(parse("map 15 --sensor 1,x").is_err());
+        assert!(parse("map 15 --route").is_err());
+        assert!(parse("dot 16 --view 1,2,3,4").is_err());
+        assert!(parse("run 15 --image field.pgm").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_trace_args() -> Result<()> {
+        assert_eq!(
+            Command::Trace(TraceArgs {
+                input: InputSource::Default,
+                cycles: DEFAULT_TRACE_CYCLES,
+                breakpoints: Vec::new(),
+                csv: false
+            }),
+            parse("trace 10")?
+        );
+        assert_eq!(
+            Command::Trace(TraceArgs {
+                input: InputSource::File("test.txt".into()),
+                cycles: 20,
+                breakpoints: vec![3, 7],
+                csv: true
+            }),
+            parse("trace 10 --csv --cycles 20 --break 3 --break=7 --input test.txt")?
+        );
+        assert!(parse("trace").is_err());
+        assert!(parse("trace 11").is_err());
+        assert!(parse("trace 10 --part 2").is_err());
+        assert!(parse("trace 10 --cycles 0").is_err());
+        assert!(parse("trace 10 --break x").is_err());
+        assert!(parse("trace 10 --format json").is_err());
+        assert!(parse("trace 10 --record").is_err());
+        assert!(parse("run 10 --csv").is_err());
+        assert!(parse("bench 10 --break 3").is_err());
+        assert!(parse("map 15 --cycles 3").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter() -> Result<()> {
+        let filter = parse_options(["1..=3,5", "-p", "2"].into_iter().map(String::from))?
+            .into_filter("list")?;
+        assert!(filter.matches(2, 2));
+        assert!(filter.matches(5, 2));
+        assert!(!filter.matches(4, 2));
+        assert!(!filter.matches(2, 1));
+        assert!(Filter::default().matches(16, 1));
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
 
 use crate::{
+    registry::Registry,
     solution::{Solution, SolutionInput},
     util::split_parse,
 };
@@ -51,6 +52,11 @@ impl Solution for Day1Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day1Pt1>();
+    registry.add::<Day1Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
     use lazy_static::lazy_static;
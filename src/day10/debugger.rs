@@ -0,0 +1,335 @@
+use anyhow::Result;
+use itertools::Itertools;
+use std::collections::BTreeSet;
+
+use super::isa::{REGISTERS, X};
+use super::{Cmd, State, Word, VM};
+
+// one completed cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRow {
+    pub cycle: usize,      // starting from 1
+    pub pc: Option<usize>, // None once the program has ended
+    pub cmd: Option<Cmd>,
+    pub regs_during: [Word; REGISTERS.len()], // what the CRT sees
+    pub regs_after: [Word; REGISTERS.len()],
+}
+
+impl TraceRow {
+    #[allow(dead_code)]
+    pub fn x_during(&self) -> Word {
+        self.regs_during[X]
+    }
+
+    #[allow(dead_code)]
+    pub fn x_after(&self) -> Word {
+        self.regs_after[X]
+    }
+}
+
+// why a run stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Cycle,             // the cycle asked for was reached
+    Condition,         // the condition became true
+    Breakpoint(usize), // the instruction at the index is about to start
+    Halted,            // there are no instructions left
+}
+
+pub struct Debugger<'a> {
+    vm: VM<'a>,
+    breakpoints: BTreeSet<usize>,
+    trace: Vec<TraceRow>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a [Cmd]) -> Debugger<'a> {
+        Debugger {
+            vm: VM::new(program),
+            breakpoints: BTreeSet::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn state(&self) -> &State {
+        &self.vm.state
+    }
+
+    #[allow(dead_code)]
+    pub fn completed_cycles(&self) -> usize {
+        self.vm.completed_cycles
+    }
+
+    // the instruction running during the next cycle
+    pub fn current(&self) -> Option<(usize, &Cmd)> {
+        self.vm.current_cmd.map(|cmd| (self.vm.state.pc, cmd))
+    }
+
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    #[allow(dead_code)]
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn trace(&self) -> &[TraceRow] {
+        &self.trace
+    }
+
+    // runs one cycle, the machine keeps cycling after the program has ended;
+    // on overflow the cycle is not traced and the registers are left as they were
+    pub fn step(&mut self) -> Result<&TraceRow> {
+        let current = self.current().map(|(pc, cmd)| (pc, cmd.clone()));
+        let regs_during = self.vm.state.regs;
+        self.vm.cycle()?;
+        self.trace.push(TraceRow {
+            cycle: self.vm.completed_cycles,
+            pc: current.as_ref().map(|(pc, _)| *pc),
+            cmd: current.map(|(_, cmd)| cmd),
+            regs_during,
+            regs_after: self.vm.state.regs,
+        });
+        Ok(self.trace.last().unwrap())
+    }
+
+    // a breakpoint is hit when the instruction is about to start, not while it runs
+    fn at_breakpoint(&self) -> Option<usize> {
+        let cmd_started = self.vm.current_cmd_end_cycle
+            == self.vm.completed_cycles + self.vm.current_cmd?.duration();
+        let pc = self.vm.state.pc;
+        (cmd_started && self.breakpoints.contains(&pc)).then_some(pc)
+    }
+
+    // runs at least one cycle, then till the cycle is completed or a breakpoint is hit
+    #[allow(dead_code)]
+    pub fn run_to_cycle(&mut self, cycle: usize) -> Result<Stop> {
+        loop {
+            self.step()?;
+            if let Some(pc) = self.at_breakpoint() {
+                return Ok(Stop::Breakpoint(pc));
+            }
+            if self.vm.completed_cycles >= cycle {
+                return Ok(Stop::Cycle);
+            }
+        }
+    }
+
+    // runs at least one cycle, then till the condition holds after a cycle, a breakpoint is
+    // hit, the program ends or max_cycle is completed
+    pub fn run_until(
+        &mut self,
+        condition: impl Fn(&State) -> bool,
+        max_cycle: usize,
+    ) -> Result<Stop> {
+        loop {
+            self.step()?;
+            if condition(&self.vm.state) {
+                return Ok(Stop::Condition);
+            }
+            if let Some(pc) = self.at_breakpoint() {
+                return Ok(Stop::Breakpoint(pc));
+            }
+            if self.vm.current_cmd.is_none() {
+                return Ok(Stop::Halted);
+            }
+            if self.vm.completed_cycles >= max_cycle {
+                return Ok(Stop::Cycle);
+            }
+        }
+    }
+}
+
+// the trace as CSV with a header line, the registers during and after each cycle
+pub fn trace_csv(trace: &[TraceRow]) -> String {
+    let header = [
+        "cycle".to_string(),
+        "pc".to_string(),
+        "instruction".to_string(),
+    ]
+    .into_iter()
+    .chain(
+        REGISTERS
+            .iter()
+            .flat_map(|reg| [format!("{}_during", reg), format!("{}_after", reg)]),
+    )
+    .join(",");
+    let rows = trace.iter().map(|row| {
+        [
+            row.cycle.to_string(),
+            row.pc.map(|pc| pc.to_string()).unwrap_or_default(),
+            row.cmd
+                .as_ref()
+                .map(|cmd| cmd.to_string())
+                .unwrap_or_default(),
+        ]
+        .into_iter()
+        .chain(
+            row.regs_during
+                .iter()
+                .zip(row.regs_after.iter())
+                .flat_map(|(during, after)| [during.to_string(), after.to_string()]),
+        )
+        .join(",")
+    });
+    [header]
+        .into_iter()
+        .chain(rows)
+        .map(|line| line + "\n")
+        .collect()
+}
+
+// the same for reading, one line per cycle with the registers during -> after it
+pub fn trace_text(trace: &[TraceRow]) -> String {
+    trace
+        .iter()
+        .map(|row| {
+            let regs = REGISTERS
+                .iter()
+                .enumerate()
+                .map(|(reg, name)| {
+                    format!(
+                        "{} {} -> {}",
+                        name, row.regs_during[reg], row.regs_after[reg]
+                    )
+                })
+                .join(", ");
+            format!(
+                "{:>4} {:>4} {:<12} {}\n",
+                row.cycle,
+                row.pc.map(|pc| pc.to_string()).unwrap_or_default(),
+                row.cmd
+                    .as_ref()
+                    .map(|cmd| cmd.to_string())
+                    .unwrap_or_default(),
+                regs
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{
+        day10::{assemble, crt_rows, Day10Pt1},
+        util::get_input,
+    };
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref INPUT_TEST: Vec<Cmd> = get_input::<Day10Pt1>("test.txt").unwrap();
+        static ref INPUT_MAIN: Vec<Cmd> = get_input::<Day10Pt1>("input.txt").unwrap();
+    }
+
+    #[test]
+    fn test_trace_csv() -> Result<()> {
+        // the small example of the puzzle
+        let program = assemble("noop\naddx 3\naddx -5")?;
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(Stop::Cycle, debugger.run_to_cycle(6)?);
+        let expected = "\
+cycle,pc,instruction,x_during,x_after,y_during,y_after
+1,0,noop,1,1,0,0
+2,1,addx 3,1,1,0,0
+3,1,addx 3,1,4,0,0
+4,2,addx -5,4,4,0,0
+5,2,addx -5,4,-1,0,0
+6,,,-1,-1,0,0
+";
+        assert_eq!(expected, trace_csv(debugger.trace()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_step() -> Result<()> {
+        let program = assemble("addx 3\nnoop")?;
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(Some((0, &program[0])), debugger.current());
+        assert_eq!(1, debugger.step()?.x_after());
+        assert_eq!(Some((0, &program[0])), debugger.current());
+        assert_eq!(4, debugger.step()?.x_after());
+        assert_eq!(Some((1, &program[1])), debugger.current());
+        assert_eq!(2, debugger.completed_cycles());
+        debugger.step()?;
+        assert_eq!(None, debugger.current());
+        Ok(())
+    }
+
+    #[test]
+    fn test_breakpoints() -> Result<()> {
+        let program = assemble("noop\naddx 1\nnoop\naddx 2\nnoop")?;
+        let mut debugger = Debugger::new(&program);
+        debugger.add_breakpoint(1);
+        debugger.add_breakpoint(3);
+        assert_eq!(Stop::Breakpoint(1), debugger.run_to_cycle(100)?);
+        assert_eq!(1, debugger.completed_cycles());
+        // the second cycle of addx 1 does not hit it again
+        assert_eq!(Stop::Breakpoint(3), debugger.run_to_cycle(100)?);
+        assert_eq!(4, debugger.completed_cycles());
+        assert_eq!(2, debugger.state().x());
+
+        assert!(debugger.remove_breakpoint(1));
+        assert!(!debugger.remove_breakpoint(1));
+        assert_eq!(Stop::Halted, debugger.run_until(|_| false, 100)?);
+        assert_eq!(7, debugger.completed_cycles());
+        assert_eq!(4, debugger.state().x());
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_until() -> Result<()> {
+        let mut debugger = Debugger::new(&INPUT_TEST);
+        assert_eq!(
+            Stop::Condition,
+            debugger.run_until(|state| state.x() < 0, 1000)?
+        );
+        let (last, before) = debugger.trace().split_last().unwrap();
+        assert!(last.x_after() < 0);
+        assert!(before.iter().all(|row| row.x_after() >= 0));
+
+        // a program which never ends
+        let program = assemble("addx 1\njmp -1")?;
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(Stop::Cycle, debugger.run_until(|_| false, 1000)?);
+        assert_eq!(1000, debugger.completed_cycles());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signal_strength() -> Result<()> {
+        let mut debugger = Debugger::new(&INPUT_TEST);
+        debugger.run_to_cycle(20)?;
+        assert_eq!(420, 20 * debugger.trace()[19].x_during());
+        Ok(())
+    }
+
+    #[test]
+    fn test_crt_from_trace() -> Result<()> {
+        for input in [&*INPUT_TEST, &*INPUT_MAIN] {
+            let mut debugger = Debugger::new(input);
+            debugger.run_to_cycle(240)?;
+            let pixels = debugger
+                .trace()
+                .iter()
+                .map(|row| {
+                    let pos = ((row.cycle - 1) % 40) as Word;
+                    if (row.x_during() - pos).abs() <= 1 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<Vec<_>>();
+            let rows = pixels
+                .chunks(40)
+                .map(|row| row.iter().collect::<String>())
+                .collect::<Vec<_>>();
+            assert_eq!(crt_rows(input)?, rows);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::{anyhow, bail, ensure, Context, Error, Result};
+use itertools::Itertools;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+pub type Word = i32;
+pub type Reg = usize;
+
+// the registers by index, x is the one of the puzzle and the only one drawn
+pub const REGISTERS: [&str; 2] = ["x", "y"];
+pub const X: Reg = 0;
+pub const Y: Reg = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    pub regs: [Word; REGISTERS.len()],
+    pub pc: usize, // the index of the instruction to run next
+}
+
+impl State {
+    pub fn new() -> State {
+        let mut regs = [0; REGISTERS.len()];
+        regs[X] = 1;
+        State { regs, pc: 0 }
+    }
+
+    pub fn x(&self) -> Word {
+        self.regs[X]
+    }
+
+    pub fn value(&self, operand: Operand) -> Word {
+        match operand {
+            Operand::Reg(reg) => self.regs[reg],
+            Operand::Imm(value) => value,
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    Reg,
+    Imm,
+    Value, // a register or a number
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Reg(Reg),
+    Imm(Word),
+}
+
+impl Operand {
+    fn parse(s: &str, kind: OperandKind) -> Result<Operand> {
+        let reg = REGISTERS.iter().position(|&name| name == s);
+        match (kind, reg) {
+            (OperandKind::Reg | OperandKind::Value, Some(reg)) => Ok(Operand::Reg(reg)),
+            (OperandKind::Reg, None) => bail!("unknown register {:?}", s),
+            (OperandKind::Imm | OperandKind::Value, _) => Ok(Operand::Imm(
+                s.parse().with_context(|| anyhow!("parsing {:?}", s))?,
+            )),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Reg(reg) => write!(f, "{}", REGISTERS[*reg]),
+            Operand::Imm(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+// where to continue after an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    Jump(Word), // relative to the instruction
+}
+
+pub struct InstrDef {
+    pub name: &'static str,
+    pub cycles: usize,
+    pub operands: &'static [OperandKind],
+    // applied when the last cycle of the instruction is completed, fails on overflow
+    pub effect: fn(&mut State, &[Operand]) -> Result<Flow>,
+}
+
+fn no_overflow(value: Option<Word>) -> Result<Word> {
+    value.ok_or_else(|| anyhow!("overflow"))
+}
+
+// adding an instruction is adding a line here
+pub static INSTRUCTION_SET: &[InstrDef] = &[
+    InstrDef {
+        name: "noop",
+        cycles: 1,
+        operands: &[],
+        effect: |_, _| Ok(Flow::Next),
+    },
+    InstrDef {
+        name: "addx",
+        cycles: 2,
+        operands: &[OperandKind::Value],
+        effect: |state, ops| {
+            state.regs[X] = no_overflow(state.regs[X].checked_add(state.value(ops[0])))?;
+            Ok(Flow::Next)
+        },
+    },
+    InstrDef {
+        name: "addy",
+        cycles: 2,
+        operands: &[OperandKind::Value],
+        effect: |state, ops| {
+            state.regs[Y] = no_overflow(state.regs[Y].checked_add(state.value(ops[0])))?;
+            Ok(Flow::Next)
+        },
+    },
+    InstrDef {
+        name: "mulx",
+        cycles: 3,
+        operands: &[OperandKind::Value],
+        effect: |state, ops| {
+            state.regs[X] = no_overflow(state.regs[X].checked_mul(state.value(ops[0])))?;
+            Ok(Flow::Next)
+        },
+    },
+    InstrDef {
+        name: "jmp",
+        cycles: 1,
+        operands: &[OperandKind::Imm],
+        effect: |state, ops| Ok(Flow::Jump(state.value(ops[0]))),
+    },
+    InstrDef {
+        name: "jnz",
+        cycles: 2,
+        operands: &[OperandKind::Reg, OperandKind::Imm],
+        effect: |state, ops| {
+            if state.value(ops[0]) != 0 {
+                Ok(Flow::Jump(state.value(ops[1])))
+            } else {
+                Ok(Flow::Next)
+            }
+        },
+    },
+];
+
+pub type Opcode = usize; // the index in INSTRUCTION_SET
+
+pub fn opcode(name: &str) -> Option<Opcode> {
+    INSTRUCTION_SET.iter().position(|def| def.name == name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cmd {
+    pub opcode: Opcode,
+    pub operands: Vec<Operand>,
+}
+
+impl Cmd {
+    pub fn def(&self) -> &'static InstrDef {
+        &INSTRUCTION_SET[self.opcode]
+    }
+
+    pub fn duration(&self) -> usize {
+        self.def().cycles
+    }
+
+    pub fn apply(&self, state: &mut State) -> Result<Flow> {
+        (self.def().effect)(state, &self.operands)
+    }
+}
+
+impl FromStr for Cmd {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Cmd> {
+        let mut words = s.split(' ');
+        let name = words.next().unwrap_or_default();
+        let opcode = opcode(name).ok_or_else(|| anyhow!("unknown instruction {:?}", name))?;
+        let words = words.collect::<Vec<_>>();
+        let kinds = INSTRUCTION_SET[opcode].operands;
+        ensure!(
+            words.len() == kinds.len(),
+            "{} takes {} operands in {:?}",
+            name,
+            kinds.len(),
+            s
+        );
+        let operands = words
+            .into_iter()
+            .zip(kinds)
+            .map(|(word, &kind)| Operand::parse(word, kind))
+            .collect::<Result<_>>()
+            .with_context(|| anyhow!("can't parse {:?}", s))?;
+        Ok(Cmd { opcode, operands })
+    }
+}
+
+// the canonical text, parsed back to the same instruction
+impl Display for Cmd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.def().name)?;
+        for operand in self.operands.iter() {
+            write!(f, " {}", operand)?;
+        }
+        Ok(())
+    }
+}
+
+// one instruction per line, empty lines are skipped
+pub fn assemble(text: &str) -> Result<Vec<Cmd>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            line.trim()
+                .parse()
+                .with_context(|| anyhow!("line {}", idx + 1))
+        })
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn disassemble(program: &[Cmd]) -> String {
+    program.iter().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::util::read_input;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        for name in ["test.txt", "input.txt"] {
+            let text = read_input(10, name)?;
+            let program = assemble(&text)?;
+            assert_eq!(text.trim_end(), disassemble(&program));
+            assert_eq!(program, assemble(&disassemble(&program))?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let program = assemble("noop\naddx -3\n\n  mulx y\njnz x -2\njmp 5")?;
+        assert_eq!(
+            vec![
+                Cmd {
+                    opcode: 0,
+                    operands: vec![]
+                },
+                Cmd {
+                    opcode: 1,
+                    operands: vec![Operand::Imm(-3)]
+                },
+                Cmd {
+                    opcode: 3,
+                    operands: vec![Operand::Reg(Y)]
+                },
+                Cmd {
+                    opcode: 5,
+                    operands: vec![Operand::Reg(X), Operand::Imm(-2)]
+                },
+                Cmd {
+                    opcode: 4,
+                    operands: vec![Operand::Imm(5)]
+                },
+            ],
+            program
+        );
+        assert_eq!(
+            "noop\naddx -3\nmulx y\njnz x -2\njmp 5",
+            disassemble(&program)
+        );
+
+        assert!("addx".parse::<Cmd>().is_err());
+        assert!("addx 1 2".parse::<Cmd>().is_err());
+        assert!("addx z".parse::<Cmd>().is_err());
+        assert!("jmp x".parse::<Cmd>().is_err());
+        assert!("subx 1".parse::<Cmd>().is_err());
+        let err = assemble("noop\nnoop 1").unwrap_err();
+        assert_eq!("line 2", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_table() -> Result<()> {
+        // the names are what the instructions are found by
+        assert!(INSTRUCTION_SET.iter().map(|def| def.name).all_unique());
+        assert_eq!(Some(1), opcode("addx"));
+        assert_eq!(2, "addx 1".parse::<Cmd>()?.duration());
+
+        let mut state = State::new();
+        let flow = "jnz x 4".parse::<Cmd>()?.apply(&mut state)?;
+        assert_eq!(Flow::Jump(4), flow);
+        let flow = "jnz y 4".parse::<Cmd>()?.apply(&mut state)?;
+        assert_eq!(Flow::Next, flow);
+        "addy 7".parse::<Cmd>()?.apply(&mut state)?;
+        "mulx y".parse::<Cmd>()?.apply(&mut state)?;
+        assert_eq!([7, 7], state.regs);
+
+        // the registers are left as they were on overflow
+        state.regs[X] = Word::MAX;
+        assert!("addx 1".parse::<Cmd>()?.apply(&mut state).is_err());
+        assert!("mulx y".parse::<Cmd>()?.apply(&mut state).is_err());
+        assert_eq!([Word::MAX, 7], state.regs);
+        Ok(())
+    }
+}
@@ -1,103 +1,94 @@
+// for finding the cycle the CRT goes wrong at, used by `aoc2022 trace 10`
+mod debugger;
+mod isa;
+mod ocr;
+
 use crate::{
+    registry::Registry,
     solution::{Solution, SolutionInput},
-    util::split_parse,
 };
-use anyhow::{anyhow, bail, Context, Error, Result};
+use anyhow::{anyhow, Context, Result};
 use std::io::Write;
-use std::str::FromStr;
-
-pub struct State {
-    pub x: i32,
-}
-
-impl State {
-    fn new() -> State {
-        State { x: 1 }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Cmd {
-    NoOp,
-    AddX(i32),
-}
-
-impl Cmd {
-    pub fn duration(&self) -> usize {
-        match self {
-            Cmd::NoOp => 1,
-            Cmd::AddX(_) => 2,
-        }
-    }
-    pub fn apply(&self, state: &mut State) {
-        match self {
-            Cmd::NoOp => {}
-            Cmd::AddX(x) => state.x += x,
-        }
-    }
-}
 
-impl FromStr for Cmd {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Cmd> {
-        let words = s.split(' ').collect::<Vec<_>>();
-        let cmd = match words[..] {
-            ["noop"] => Cmd::NoOp,
-            ["addx", x_str] => Cmd::AddX(
-                x_str
-                    .parse()
-                    .with_context(|| anyhow!("parsing {:?}", x_str))?,
-            ),
-            _ => bail!("can't parse {:?}", s),
-        };
-        Ok(cmd)
-    }
-}
+use self::debugger::{trace_csv, trace_text, Debugger};
+pub use debugger::Stop;
+pub use isa::{assemble, Cmd, Flow, State, Word};
 
-struct VM<TProgram> {
-    program: TProgram,
+struct VM<'a> {
+    program: &'a [Cmd],
     state: State,
-    current_cmd: Option<Cmd>,
+    current_cmd: Option<&'a Cmd>,
     current_cmd_end_cycle: usize,
-    signal_strength: i32,
+    last_cycle_x: Word, // x during the last completed cycle
     completed_cycles: usize,
 }
 
-impl<TProgram: Iterator<Item = Cmd>> VM<TProgram> {
-    pub fn new(program: TProgram) -> VM<TProgram> {
+impl<'a> VM<'a> {
+    pub fn new(program: &'a [Cmd]) -> VM<'a> {
         let mut vm = VM {
             program,
             state: State::new(),
             current_cmd: None,
             current_cmd_end_cycle: 0,
-            signal_strength: 0,
+            last_cycle_x: 0,
             completed_cycles: 0,
         };
         vm.take_next_cmd();
         vm
     }
 
-    pub fn cycle(&mut self) {
+    // the signal strength during the last completed cycle
+    pub fn signal_strength(&self) -> Result<Word> {
+        Word::try_from(self.completed_cycles)
+            .ok()
+            .and_then(|cycle| cycle.checked_mul(self.last_cycle_x))
+            .ok_or_else(|| {
+                anyhow!(
+                    "signal strength overflows at cycle {} with x = {}",
+                    self.completed_cycles,
+                    self.last_cycle_x
+                )
+            })
+    }
+
+    pub fn cycle(&mut self) -> Result<()> {
         self.completed_cycles += 1;
-        self.signal_strength = self.completed_cycles as i32 * self.state.x;
+        self.last_cycle_x = self.state.x();
 
         if let Some(cmd) = self.current_cmd {
             if self.completed_cycles == self.current_cmd_end_cycle {
-                cmd.apply(&mut self.state);
+                let flow = cmd.apply(&mut self.state).with_context(|| {
+                    anyhow!(
+                        "pc {}, cycle {}: {}",
+                        self.state.pc,
+                        self.completed_cycles,
+                        cmd
+                    )
+                })?;
+                match flow {
+                    Flow::Next => self.state.pc += 1,
+                    // a jump out of the program ends it
+                    Flow::Jump(offset) => {
+                        self.state.pc = (self.state.pc as isize + offset as isize)
+                            .try_into()
+                            .unwrap_or(usize::MAX)
+                    }
+                }
                 self.take_next_cmd();
             }
         }
+        Ok(())
     }
 
-    pub fn cycle_until(&mut self, completed_cycles: usize) {
+    pub fn cycle_until(&mut self, completed_cycles: usize) -> Result<()> {
         while self.completed_cycles < completed_cycles {
-            self.cycle();
+            self.cycle()?;
         }
+        Ok(())
     }
 
     fn take_next_cmd(&mut self) {
-        self.current_cmd = self.program.next();
+        self.current_cmd = self.program.get(self.state.pc);
         self.current_cmd_end_cycle =
             self.completed_cycles + self.current_cmd.map(|cmd| cmd.duration()).unwrap_or(0)
     }
@@ -105,7 +96,7 @@ impl<TProgram: Iterator<Item = Cmd>> VM<TProgram> {
 
 impl SolutionInput for Vec<Cmd> {
     fn parse(input_str: &str) -> Result<Self> {
-        split_parse(input_str, "\n")
+        assemble(input_str)
     }
 }
 
@@ -115,59 +106,87 @@ impl Solution for Day10Pt1 {
     const PART: usize = 1;
 
     type TInput = Vec<Cmd>;
-    type TOutput = i32;
+    type TOutput = Word;
 
     fn solve(_input: &Self::TInput) -> Result<Self::TOutput> {
-        let mut vm = VM::new(_input.iter().cloned());
+        let mut vm = VM::new(_input);
         let mut sum = 0;
 
         for cycle in (20..=220).step_by(40) {
-            vm.cycle_until(cycle);
-            sum += vm.signal_strength;
+            vm.cycle_until(cycle)?;
+            sum = vm
+                .signal_strength()?
+                .checked_add(sum)
+                .ok_or_else(|| anyhow!("the sum of the signal strengths overflows"))?;
         }
 
         Ok(sum)
     }
 }
 
+// the pixels drawn by the CRT, 6 rows of 40 '#' or '.'
+pub fn crt_rows(program: &[Cmd]) -> Result<Vec<String>> {
+    let mut vm = VM::new(program);
+    let mut out = Vec::new();
+
+    for _ in 0..6 {
+        let mut line = Vec::new();
+        for pos in 0..40 {
+            let pixel_lit = vm.state.x().abs_diff(pos) <= 1;
+            write!(line, "{}", if pixel_lit { '#' } else { '.' })?;
+            vm.cycle()?;
+        }
+        out.push(String::from_utf8(line)?);
+    }
+
+    Ok(out)
+}
+
 pub struct Day10Pt2;
 impl Solution for Day10Pt2 {
     const DAY: usize = 10;
-    const PART: usize = 1;
+    const PART: usize = 2;
 
     type TInput = Vec<Cmd>;
-    type TOutput = Vec<String>;
+    type TOutput = String;
 
     fn solve(_input: &Self::TInput) -> Result<Self::TOutput> {
-        let mut vm = VM::new(_input.iter().cloned());
-        let mut out = Vec::new();
-
-        for _ in 0..6 {
-            let mut line = Vec::new();
-            for pos in 0..40 {
-                let pixel_lit = (-1..=1).contains(&(vm.state.x - pos));
-                write!(line, "{}", if pixel_lit { '#' } else { '.' })?;
-                vm.cycle();
-            }
-            out.push(String::from_utf8(line)?);
-        }
-
-        Ok(out)
+        ocr::decode(&crt_rows(_input)?)
     }
+}
 
-    fn print_result(result: &Self::TOutput) {
-        println!();
-        for line in result.iter() {
-            println!("{}", line);
-        }
+// the trace for `aoc2022 trace 10`, as CSV or as text, and why it ended: after `cycles`,
+// when the program is over or when one of the breakpoints is about to run
+pub fn trace(
+    input_str: &str,
+    cycles: usize,
+    breakpoints: &[usize],
+    csv: bool,
+) -> Result<(String, Stop)> {
+    let program = assemble(input_str)?;
+    let mut debugger = Debugger::new(&program);
+    for &pc in breakpoints.iter() {
+        debugger.add_breakpoint(pc);
     }
+    let stop = debugger.run_until(|_| false, cycles)?;
+    let trace = if csv {
+        trace_csv(debugger.trace())
+    } else {
+        trace_text(debugger.trace())
+    };
+    Ok((trace, stop))
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day10Pt1>();
+    registry.add::<Day10Pt2>();
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::util::get_input;
+    use crate::util::{get_input, read_input};
     use lazy_static::lazy_static;
 
     lazy_static! {
@@ -177,6 +196,7 @@ mod tests {
 
     #[test]
     fn test_part2_result() -> Result<()> {
+        assert_eq!("PAPJCBHP", Day10Pt2::solve(&INPUT_MAIN)?);
         assert_eq!(
             &vec![
                 "###...##..###....##..##..###..#..#.###..".to_string(),
@@ -186,7 +206,7 @@ mod tests {
                 "#....#..#.#....#..#.#..#.#..#.#..#.#....".to_string(),
                 "#....#..#.#.....##...##..###..#..#.#....".to_string(),
             ],
-            &Day10Pt2::solve(&INPUT_MAIN)?
+            &crt_rows(&INPUT_MAIN)?
         );
         Ok(())
     }
@@ -202,8 +222,10 @@ mod tests {
                 "######......######......######......####".to_string(),
                 "#######.......#######.......#######.....".to_string(),
             ],
-            &Day10Pt2::solve(&INPUT_TEST)?
+            &crt_rows(&INPUT_TEST)?
         );
+        // the example draws stripes, not letters
+        assert!(Day10Pt2::solve(&INPUT_TEST).is_err());
         Ok(())
     }
 
@@ -221,72 +243,135 @@ mod tests {
 
     #[test]
     fn test_signal_strength() -> Result<()> {
-        let mut vm = VM::new(INPUT_TEST.iter().cloned());
+        let mut vm = VM::new(&INPUT_TEST);
 
-        vm.cycle_until(20);
-        assert_eq!(420, vm.signal_strength);
+        vm.cycle_until(20)?;
+        assert_eq!(420, vm.signal_strength()?);
 
-        vm.cycle_until(60);
-        assert_eq!(1140, vm.signal_strength);
+        vm.cycle_until(60)?;
+        assert_eq!(1140, vm.signal_strength()?);
 
-        vm.cycle_until(100);
-        assert_eq!(1800, vm.signal_strength);
+        vm.cycle_until(100)?;
+        assert_eq!(1800, vm.signal_strength()?);
 
-        vm.cycle_until(140);
-        assert_eq!(2940, vm.signal_strength);
+        vm.cycle_until(140)?;
+        assert_eq!(2940, vm.signal_strength()?);
 
-        vm.cycle_until(180);
-        assert_eq!(2880, vm.signal_strength);
+        vm.cycle_until(180)?;
+        assert_eq!(2880, vm.signal_strength()?);
 
-        vm.cycle_until(220);
-        assert_eq!(3960, vm.signal_strength);
+        vm.cycle_until(220)?;
+        assert_eq!(3960, vm.signal_strength()?);
 
         Ok(())
     }
 
     #[test]
     fn test_vm_cycle() -> Result<()> {
-        let program = vec![Cmd::NoOp, Cmd::AddX(3), Cmd::AddX(-5)];
-        let mut vm = VM::new(program.iter().cloned());
+        let program = assemble("noop\naddx 3\naddx -5")?;
+        let mut vm = VM::new(&program);
 
-        assert_eq!(Some(Cmd::NoOp), vm.current_cmd);
+        assert_eq!(Some(&program[0]), vm.current_cmd);
 
-        vm.cycle();
+        vm.cycle()?;
 
         assert_eq!(1, vm.completed_cycles);
-        assert_eq!(1, vm.state.x);
-        assert_eq!(Some(Cmd::AddX(3)), vm.current_cmd);
+        assert_eq!(1, vm.state.x());
+        assert_eq!(Some(&program[1]), vm.current_cmd);
 
-        vm.cycle();
+        vm.cycle()?;
 
         assert_eq!(2, vm.completed_cycles);
-        assert_eq!(1, vm.state.x);
-        assert_eq!(Some(Cmd::AddX(3)), vm.current_cmd);
+        assert_eq!(1, vm.state.x());
+        assert_eq!(Some(&program[1]), vm.current_cmd);
 
-        vm.cycle();
+        vm.cycle()?;
 
         assert_eq!(3, vm.completed_cycles);
-        assert_eq!(4, vm.state.x);
-        assert_eq!(Some(Cmd::AddX(-5)), vm.current_cmd);
+        assert_eq!(4, vm.state.x());
+        assert_eq!(Some(&program[2]), vm.current_cmd);
 
-        vm.cycle();
+        vm.cycle()?;
 
         assert_eq!(4, vm.completed_cycles);
-        assert_eq!(4, vm.state.x);
-        assert_eq!(Some(Cmd::AddX(-5)), vm.current_cmd);
+        assert_eq!(4, vm.state.x());
+        assert_eq!(Some(&program[2]), vm.current_cmd);
 
-        vm.cycle();
+        vm.cycle()?;
 
         assert_eq!(5, vm.completed_cycles);
-        assert_eq!(-1, vm.state.x);
+        assert_eq!(-1, vm.state.x());
         assert_eq!(None, vm.current_cmd);
 
-        vm.cycle();
+        vm.cycle()?;
 
         assert_eq!(6, vm.completed_cycles);
-        assert_eq!(-1, vm.state.x);
+        assert_eq!(-1, vm.state.x());
+        assert_eq!(None, vm.current_cmd);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vm_jumps() -> Result<()> {
+        // x += 2 for y from 3 down to 1, then x *= x
+        let program = assemble("addy 3\naddx 2\naddy -1\njnz y -2\nmulx x\njmp -10")?;
+        let mut vm = VM::new(&program);
+        // 2 + 3 * (2 + 2 + 2) cycles, the last jnz goes on to mulx
+        vm.cycle_until(20)?;
+        assert_eq!([7, 0], vm.state.regs);
+        assert_eq!(Some(&program[4]), vm.current_cmd);
+        vm.cycle_until(23)?;
+        assert_eq!(49, vm.state.x());
+        assert_eq!(Some(&program[5]), vm.current_cmd);
+        // the jump leaves the program
+        vm.cycle_until(24)?;
         assert_eq!(None, vm.current_cmd);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow() -> Result<()> {
+        let program = assemble(&format!("addx 1\n{}", ["mulx x"; 6].join("\n")))?;
+        let err = Day10Pt1::solve(&program).unwrap_err();
+        assert_eq!("pc 5, cycle 17: mulx x: overflow", format!("{:#}", err));
+
+        let program = assemble("addx 2000000000")?;
+        let err = Day10Pt1::solve(&program).unwrap_err();
+        assert_eq!(
+            "signal strength overflows at cycle 20 with x = 2000000001",
+            err.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace() -> Result<()> {
+        let input_str = "noop\naddx 3\naddx -5";
+        let (csv, stop) = trace(input_str, 240, &[], true)?;
+        assert_eq!(Stop::Halted, stop);
+        assert_eq!(
+            "cycle,pc,instruction,x_during,x_after,y_during,y_after\n1,0,noop,1,1,0,0\n",
+            csv.lines()
+                .take(2)
+                .map(|line| format!("{}\n", line))
+                .collect::<String>()
+        );
+        assert_eq!(1 + 5, csv.lines().count());
+
+        let (text, stop) = trace(input_str, 240, &[2], false)?;
+        assert_eq!(Stop::Breakpoint(2), stop);
+        assert_eq!(
+            "   1    0 noop         x 1 -> 1, y 0 -> 0\n   \
+               2    1 addx 3       x 1 -> 1, y 0 -> 0\n   \
+               3    1 addx 3       x 1 -> 4, y 0 -> 0\n",
+            text
+        );
 
+        let (text, stop) = trace(&read_input(10, "test.txt")?, 20, &[], false)?;
+        assert_eq!(Stop::Cycle, stop);
+        assert_eq!(20, text.lines().count());
+        assert!(trace("addz 1", 240, &[], false).is_err());
         Ok(())
     }
 }
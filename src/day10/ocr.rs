@@ -0,0 +1,168 @@
+use anyhow::{anyhow, ensure, Result};
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+// the glyphs are separated by one empty column
+const CELL_WIDTH: usize = GLYPH_WIDTH + 1;
+
+// the letters the puzzles are known to draw, row by row
+const FONT: [(char, &str); 16] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+fn glyph_to_char(glyph: &str) -> Option<char> {
+    FONT.iter()
+        .find(|(_, pixels)| *pixels == glyph)
+        .map(|&(letter, _)| letter)
+}
+
+// the CRT rows of '#' and '.' as capital letters
+pub fn decode(rows: &[String]) -> Result<String> {
+    ensure!(
+        rows.len() == GLYPH_HEIGHT,
+        "expected {} rows, got {}",
+        GLYPH_HEIGHT,
+        rows.len()
+    );
+    let width = rows[0].len();
+    ensure!(
+        rows.iter().all(|row| row.len() == width),
+        "the rows are not of the same length"
+    );
+    ensure!(
+        rows.iter()
+            .all(|row| row.chars().all(|c| c == '#' || c == '.')),
+        "only '#' and '.' are expected"
+    );
+    // the last empty column may be cut off
+    ensure!(
+        width.is_multiple_of(CELL_WIDTH) || width % CELL_WIDTH == GLYPH_WIDTH,
+        "width {} is not a whole number of letters",
+        width
+    );
+
+    (0..width.div_ceil(CELL_WIDTH))
+        .map(|idx| {
+            let start = idx * CELL_WIDTH;
+            let glyph = rows
+                .iter()
+                .map(|row| &row[start..start + GLYPH_WIDTH])
+                .collect::<String>();
+            let gap_empty = rows
+                .iter()
+                .all(|row| row[start + GLYPH_WIDTH..].chars().next().unwrap_or('.') == '.');
+            gap_empty
+                .then(|| glyph_to_char(&glyph))
+                .flatten()
+                .ok_or_else(|| {
+                    let picture = rows
+                        .iter()
+                        .map(|row| &row[start..(start + CELL_WIDTH).min(width)])
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    anyhow!(
+                        "unknown glyph {} at columns {}..{}:\n{}",
+                        idx + 1,
+                        start,
+                        start + GLYPH_WIDTH,
+                        picture
+                    )
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn rows(s: &str) -> Vec<String> {
+        s.lines().map(|it| it.to_string()).collect()
+    }
+
+    // the letters in the puzzle layout
+    fn render(letters: &str) -> Vec<String> {
+        (0..GLYPH_HEIGHT)
+            .map(|y| {
+                letters
+                    .chars()
+                    .map(|letter| {
+                        let (_, pixels) = FONT.iter().find(|(c, _)| *c == letter).unwrap();
+                        format!("{}.", &pixels[y * GLYPH_WIDTH..(y + 1) * GLYPH_WIDTH])
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_font() -> Result<()> {
+        let letters = FONT.iter().map(|(letter, _)| letter).collect::<String>();
+        assert_eq!(letters, decode(&render(&letters))?);
+        assert_eq!("EHZFZHCZ", decode(&render("EHZFZHCZ"))?);
+
+        // without the last empty column
+        let mut rows = render("HELLO");
+        rows.iter_mut().for_each(|row| {
+            row.pop();
+        });
+        assert_eq!("HELLO", decode(&rows)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = decode(&rows(
+            "\
+.##..#...
+#..#.#...
+#..#.#...
+####.#...
+#..#.#...
+#..#.###.",
+        ))
+        .unwrap_err();
+        assert_eq!(
+            "unknown glyph 2 at columns 5..9:\n#...\n#...\n#...\n#...\n#...\n###.",
+            err.to_string()
+        );
+
+        // a pixel between the letters
+        let mut rows = render("AB");
+        rows[2].replace_range(4..5, "#");
+        assert!(decode(&rows).is_err());
+
+        let rows = render("AB");
+        assert!(decode(&rows[1..]).is_err());
+        assert!(decode(
+            &rows
+                .iter()
+                .map(|it| it[..7].to_string())
+                .collect::<Vec<_>>()
+        )
+        .is_err());
+        assert!(decode(
+            &rows
+                .iter()
+                .map(|it| it.replace('#', "X"))
+                .collect::<Vec<_>>()
+        )
+        .is_err());
+    }
+}
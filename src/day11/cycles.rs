@@ -0,0 +1,154 @@
+use anyhow::{ensure, Result};
+use std::collections::HashMap;
+
+use super::{apply_op_to_item, modular_divisor, monkey_business_of, MonkeyInput, MonkeyName, Num};
+
+// the items never affect each other, so every item can be followed on its own;
+// with the worry level kept modulo the product of the divisors, an item has a finite
+// number of states, and sooner or later it comes back to a state it was in before
+
+type State = (MonkeyName, Num); // the monkey holding the item and its worry level
+
+// one round for one item: it is inspected by the monkey holding it, and again by every
+// monkey it is thrown to, till it is thrown to a monkey which has already had its turn
+fn item_round(
+    input: &[MonkeyInput],
+    (mut monkey, mut worry): State,
+    divisor: Num,
+    inspection_count: &mut [usize],
+) -> Result<State> {
+    loop {
+        let rule = &input[monkey].rule;
+        inspection_count[monkey] += 1;
+        apply_op_to_item(&mut worry, &rule.op)?;
+        worry = worry.rem_euclid(divisor);
+        let (_, target) = rule.throw(|d| worry % d == 0);
+        if target <= monkey {
+            return Ok((target, worry));
+        }
+        monkey = target;
+    }
+}
+
+// inspection counts by monkey for the rounds of one item
+fn item_inspection_counts(
+    input: &[MonkeyInput],
+    start: State,
+    divisor: Num,
+    rounds: usize,
+) -> Result<Vec<usize>> {
+    // the counts after every round made, and the round each state was first seen after
+    let mut counts = vec![vec![0; input.len()]];
+    let mut seen = HashMap::from([(start, 0)]);
+    let mut state = start;
+    for round in 1..=rounds {
+        let mut count = counts[round - 1].clone();
+        state = item_round(input, state, divisor, &mut count)?;
+        counts.push(count);
+
+        if let Some(&cycle_start) = seen.get(&state) {
+            // the rounds from cycle_start to round repeat forever
+            let period = round - cycle_start;
+            let cycles = (rounds - cycle_start) / period;
+            let rest = (rounds - cycle_start) % period;
+            return Ok((0..input.len())
+                .map(|monkey| {
+                    let per_cycle = counts[round][monkey] - counts[cycle_start][monkey];
+                    counts[cycle_start + rest][monkey] + cycles * per_cycle
+                })
+                .collect());
+        }
+        seen.insert(state, round);
+    }
+    Ok(counts.pop().unwrap_or_default())
+}
+
+// inspection counts by monkey after the rounds with the part 2 rules,
+// any number of rounds takes about the same time
+#[allow(dead_code)]
+pub fn inspection_counts(input: &[MonkeyInput], rounds: usize) -> Result<Vec<usize>> {
+    ensure!(
+        input
+            .iter()
+            .enumerate()
+            .all(|(idx, monkey)| idx == monkey.name),
+        "monkeys must be listed in order"
+    );
+    let divisor = modular_divisor(input)?;
+    let mut result = vec![0; input.len()];
+    for (monkey, input_monkey) in input.iter().enumerate() {
+        for &item in input_monkey.starting_items.iter() {
+            let counts =
+                item_inspection_counts(input, (monkey, item.rem_euclid(divisor)), divisor, rounds)?;
+            for (total, count) in result.iter_mut().zip(counts) {
+                *total += count;
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[allow(dead_code)]
+pub fn monkey_business_after(input: &[MonkeyInput], rounds: usize) -> Result<usize> {
+    monkey_business_of(&inspection_counts(input, rounds)?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{
+        day11::{simulate_inspection_counts, Day11Pt1, Day11Pt2, Relief},
+        solution::Solution,
+        util::get_input,
+    };
+    use lazy_static::lazy_static;
+    use ntest::timeout;
+
+    lazy_static! {
+        static ref INPUT_TEST: Vec<MonkeyInput> = get_input::<Day11Pt1>("test.txt").unwrap();
+        static ref INPUT_MAIN: Vec<MonkeyInput> = get_input::<Day11Pt1>("input.txt").unwrap();
+    }
+
+    #[test]
+    fn test_against_simulation() -> Result<()> {
+        for input in [&*INPUT_TEST, &*INPUT_MAIN] {
+            let relief = Relief::Modulo(modular_divisor(input)?);
+            for rounds in [0, 1, 2, 20, 100, 1000, 2345] {
+                assert_eq!(
+                    simulate_inspection_counts::<Num>(input, rounds, relief)?,
+                    inspection_counts(input, rounds)?,
+                    "{} rounds",
+                    rounds
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_rounds() -> Result<()> {
+        // the example of the puzzle
+        assert_eq!(
+            vec![52166, 47830, 1938, 52013],
+            inspection_counts(&INPUT_TEST, 10000)?
+        );
+        assert_eq!(
+            Day11Pt2::solve(&INPUT_MAIN)?,
+            monkey_business_after(&INPUT_MAIN, 10000)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(1000)]
+    fn test_huge_rounds() -> Result<()> {
+        for input in [&*INPUT_TEST, &*INPUT_MAIN] {
+            let counts = inspection_counts(input, 1_000_000_000)?;
+            // every item is inspected at least once every round
+            let items: usize = input.iter().map(|it| it.starting_items.len()).sum();
+            assert!(counts.iter().sum::<usize>() >= items * 1_000_000_000);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, ensure, Result};
+use std::fmt::{self, Display};
+
+use super::{Num, Worry};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OpType {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl OpType {
+    // the operators with the higher precedence are applied first
+    fn precedence(&self) -> usize {
+        match self {
+            Self::Add | Self::Sub => 1,
+            Self::Mul | Self::Div => 2,
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Sub => '-',
+            Self::Mul => '*',
+            Self::Div => '/',
+        }
+    }
+}
+
+// the right side of "new = ...", old is the worry level before the operation
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expr {
+    Old,
+    Num(Num),
+    BinOp(Box<Expr>, OpType, Box<Expr>),
+}
+
+impl Expr {
+    pub fn bin(lhs: Expr, op: OpType, rhs: Expr) -> Expr {
+        Expr::BinOp(Box::new(lhs), op, Box::new(rhs))
+    }
+
+    pub fn eval<W: Worry>(&self, old: &W) -> Result<W> {
+        match self {
+            Self::Old => Ok(old.clone()),
+            Self::Num(num) => Ok(W::from_num(*num)),
+            Self::BinOp(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.eval(old)?, rhs.eval(old)?);
+                ensure!(
+                    *op != OpType::Div || !rhs.is_zero(),
+                    "division by zero in {} with old = {}",
+                    self,
+                    old
+                );
+                lhs.checked_op(*op, &rhs)
+                    .ok_or_else(|| anyhow!("overflow in {} with old = {}", self, old))
+            }
+        }
+    }
+
+    // the result modulo m does not change if old is replaced by anything with the same
+    // remainder, so the worry levels can be kept small; division breaks it
+    pub fn is_modular(&self) -> bool {
+        match self {
+            Self::Old | Self::Num(_) => true,
+            Self::BinOp(_, OpType::Div, _) => false,
+            Self::BinOp(lhs, _, rhs) => lhs.is_modular() && rhs.is_modular(),
+        }
+    }
+
+    fn precedence(&self) -> usize {
+        match self {
+            Self::BinOp(_, op, _) => op.precedence(),
+            _ => usize::MAX,
+        }
+    }
+}
+
+// with only the parentheses needed to parse it back the same
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Old => write!(f, "old"),
+            Self::Num(num) => write!(f, "{}", num),
+            Self::BinOp(lhs, op, rhs) => {
+                if lhs.precedence() < op.precedence() {
+                    write!(f, "({})", lhs)?;
+                } else {
+                    write!(f, "{}", lhs)?;
+                }
+                write!(f, " {} ", op.symbol())?;
+                // the operators are left associative
+                if rhs.precedence() <= op.precedence() {
+                    write!(f, "({})", rhs)
+                } else {
+                    write!(f, "{}", rhs)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use OpType::*;
+
+    #[test]
+    fn test_eval() -> Result<()> {
+        // old * old + 3
+        let expr = Expr::bin(Expr::bin(Expr::Old, Mul, Expr::Old), Add, Expr::Num(3));
+        assert_eq!(52, expr.eval(&7)?);
+        // (old - 2) / 5
+        let expr = Expr::bin(Expr::bin(Expr::Old, Sub, Expr::Num(2)), Div, Expr::Num(5));
+        assert_eq!(3, expr.eval(&19)?);
+        assert_eq!(0, expr.eval(&3)?);
+
+        let expr = Expr::bin(Expr::Num(1), Div, Expr::bin(Expr::Old, Sub, Expr::Num(1)));
+        assert!(expr.eval(&1).is_err());
+        assert!(Expr::bin(Expr::Old, Mul, Expr::Old)
+            .eval(&(Num::MAX / 2))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() {
+        let expr = Expr::bin(Expr::bin(Expr::Old, Sub, Expr::Num(2)), Div, Expr::Num(5));
+        assert_eq!("(old - 2) / 5", expr.to_string());
+        let expr = Expr::bin(Expr::Old, Sub, Expr::bin(Expr::Num(2), Sub, Expr::Old));
+        assert_eq!("old - (2 - old)", expr.to_string());
+        let expr = Expr::bin(Expr::bin(Expr::Old, Mul, Expr::Num(2)), Add, Expr::Num(-3));
+        assert_eq!("old * 2 + -3", expr.to_string());
+    }
+
+    #[test]
+    fn test_is_modular() {
+        assert!(Expr::bin(Expr::bin(Expr::Old, Mul, Expr::Old), Sub, Expr::Num(3)).is_modular());
+        assert!(
+            !Expr::bin(Expr::Num(3), Add, Expr::bin(Expr::Old, Div, Expr::Num(2))).is_modular()
+        );
+    }
+}
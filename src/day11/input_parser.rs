@@ -1,43 +1,26 @@
 use super::*;
 use anyhow::{bail, Error, Result};
 use nom::{
+    branch::alt,
     bytes::complete::{tag, take},
-    character::{
-        complete::{digit1, multispace0, multispace1, one_of},
-        streaming::space0,
-    },
-    combinator::{map, map_res, opt, recognize},
+    character::complete::{char, digit1, multispace0, multispace1, one_of, space0},
+    combinator::{map, map_res, opt, recognize, verify},
     error::ParseError,
-    multi::separated_list0,
-    sequence::{delimited, preceded, tuple},
+    multi::{many0, many1, separated_list0},
+    sequence::{delimited, pair, preceded, tuple},
     IResult,
 };
 use std::str::FromStr;
 
-impl OpVal {
-    fn parse_old(s: &str) -> IResult<&str, OpVal> {
-        map(tag("old"), |_| OpVal::Old)(s)
-    }
-
-    fn parse_num(s: &str) -> IResult<&str, OpVal> {
-        map_res(
-            recognize(tuple((opt(one_of("+-")), digit1))),
-            |s: &str| -> Result<OpVal> { Ok(OpVal::Num(s.parse()?)) },
-        )(s)
-    }
-
-    pub fn parse(s: &str) -> IResult<&str, OpVal> {
-        nom::branch::alt((Self::parse_old, Self::parse_num))(s)
-    }
-}
-
 impl FromStr for OpType {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "+" => OpType::Add,
+            "-" => OpType::Sub,
             "*" => OpType::Mul,
+            "/" => OpType::Div,
             _ => bail!("unexpected OpType {:?}", s),
         })
     }
@@ -49,12 +32,59 @@ impl OpType {
     }
 }
 
-pub fn parse_op(s: &str) -> IResult<&str, Op> {
-    tuple((
-        OpVal::parse,
-        preceded(space0, OpType::parse),
-        preceded(space0, OpVal::parse),
-    ))(s)
+// the usual precedence: expr is terms with + and -, term is factors with * and /
+impl Expr {
+    fn parse_old(s: &str) -> IResult<&str, Expr> {
+        map(tag("old"), |_| Expr::Old)(s)
+    }
+
+    fn parse_num(s: &str) -> IResult<&str, Expr> {
+        map_res(
+            recognize(tuple((opt(one_of("+-")), digit1))),
+            |s: &str| -> Result<Expr> { Ok(Expr::Num(s.parse()?)) },
+        )(s)
+    }
+
+    fn parse_factor(s: &str) -> IResult<&str, Expr> {
+        alt((
+            Self::parse_old,
+            Self::parse_num,
+            delimited(
+                pair(char('('), space0),
+                Self::parse,
+                pair(space0, char(')')),
+            ),
+        ))(s)
+    }
+
+    // left associative chain of the operands with the operators
+    fn parse_chain<'a>(
+        s: &'a str,
+        operators: &'static [OpType],
+        operand: fn(&'a str) -> IResult<&'a str, Expr>,
+    ) -> IResult<&'a str, Expr> {
+        let (s, first) = operand(s)?;
+        let (s, rest) = many0(pair(
+            preceded(space0, verify(OpType::parse, |op| operators.contains(op))),
+            preceded(space0, operand),
+        ))(s)?;
+        let expr = rest
+            .into_iter()
+            .fold(first, |lhs, (op, rhs)| Expr::bin(lhs, op, rhs));
+        Ok((s, expr))
+    }
+
+    fn parse_term(s: &str) -> IResult<&str, Expr> {
+        Self::parse_chain(s, &[OpType::Mul, OpType::Div], Self::parse_factor)
+    }
+
+    pub fn parse(s: &str) -> IResult<&str, Expr> {
+        Self::parse_chain(s, &[OpType::Add, OpType::Sub], Self::parse_term)
+    }
+}
+
+pub fn parse_op(s: &str) -> IResult<&str, Expr> {
+    Expr::parse(s)
 }
 
 pub fn spaced_tag<'a, Error: ParseError<&'a str>>(
@@ -76,6 +106,23 @@ impl MonkeyInput {
         separated_list0(tuple((space0, tag(","), space0)), Self::parse_num)(s)
     }
 
+    fn parse_test(s: &str) -> IResult<&str, MonkeyTest> {
+        map(
+            pair(
+                preceded(spaced_tag("Test: divisible by"), Self::parse_num),
+                preceded(
+                    spaced_tag("If true: throw to monkey"),
+                    Self::parse_monkey_name,
+                ),
+            ),
+            |(divisible_by, monkey_if_true)| MonkeyTest {
+                divisible_by,
+                monkey_if_true,
+            },
+        )(s)
+    }
+
+    // the tests are the ones of the puzzle, repeated before the single "If false" line
     pub fn parse(s: &str) -> IResult<&str, MonkeyInput> {
         map(
             tuple((
@@ -86,23 +133,18 @@ impl MonkeyInput {
                 ),
                 preceded(spaced_tag("Starting items:"), Self::parse_items),
                 preceded(spaced_tag("Operation: new ="), parse_op),
-                preceded(spaced_tag("Test: divisible by"), Self::parse_num),
-                preceded(
-                    spaced_tag("If true: throw to monkey"),
-                    Self::parse_monkey_name,
-                ),
+                many1(Self::parse_test),
                 preceded(
                     spaced_tag("If false: throw to monkey"),
                     Self::parse_monkey_name,
                 ),
             )),
-            |(name, items, op, test, monkey_if_true, monkey_if_false)| MonkeyInput {
+            |(name, items, op, tests, monkey_if_false)| MonkeyInput {
                 name,
                 starting_items: items,
                 rule: MonkeyRule {
                     op,
-                    divisible_by: test,
-                    monkey_if_true,
+                    tests,
                     monkey_if_false,
                 },
             },
@@ -113,7 +155,10 @@ impl MonkeyInput {
         map_res(separated_list0(multispace1, Self::parse), |monkeys| {
             for (i, monkey) in monkeys.iter().enumerate() {
                 ensure!(i == monkey.name);
-                ensure!(monkey.rule.monkey_if_true < monkeys.len());
+                for test in monkey.rule.tests.iter() {
+                    ensure!(test.divisible_by > 0);
+                    ensure!(test.monkey_if_true < monkeys.len());
+                }
                 ensure!(monkey.rule.monkey_if_false < monkeys.len());
             }
             Ok(monkeys)
@@ -152,9 +197,11 @@ mod tests {
                         name: 0,
                         starting_items: vec![79, 98],
                         rule: MonkeyRule {
-                            op: (OpVal::Old, OpType::Mul, OpVal::Num(19)),
-                            divisible_by: 23,
-                            monkey_if_true: 1,
+                            op: Expr::bin(Expr::Old, OpType::Mul, Expr::Num(19)),
+                            tests: vec![MonkeyTest {
+                                divisible_by: 23,
+                                monkey_if_true: 1
+                            }],
                             monkey_if_false: 0
                         }
                     },
@@ -162,9 +209,11 @@ mod tests {
                         name: 1,
                         starting_items: vec![79, 98],
                         rule: MonkeyRule {
-                            op: (OpVal::Old, OpType::Mul, OpVal::Num(19)),
-                            divisible_by: 23,
-                            monkey_if_true: 0,
+                            op: Expr::bin(Expr::Old, OpType::Mul, Expr::Num(19)),
+                            tests: vec![MonkeyTest {
+                                divisible_by: 23,
+                                monkey_if_true: 0
+                            }],
                             monkey_if_false: 1
                         }
                     },
@@ -192,9 +241,11 @@ mod tests {
                     name: 0,
                     starting_items: vec![79, 98],
                     rule: MonkeyRule {
-                        op: (OpVal::Old, OpType::Mul, OpVal::Num(19)),
-                        divisible_by: 23,
-                        monkey_if_true: 2,
+                        op: Expr::bin(Expr::Old, OpType::Mul, Expr::Num(19)),
+                        tests: vec![MonkeyTest {
+                            divisible_by: 23,
+                            monkey_if_true: 2
+                        }],
                         monkey_if_false: 3
                     }
                 }
@@ -202,39 +253,111 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_several_tests() {
+        let s = [
+            "Monkey 0:",
+            "  Starting items: 79",
+            "  Operation: new = old * 19",
+            "  Test: divisible by 23",
+            "    If true: throw to monkey 2",
+            "  Test: divisible by 5",
+            "    If true: throw to monkey 1",
+            "    If false: throw to monkey 3",
+        ]
+        .join("\n");
+        let (rem, monkey) = MonkeyInput::parse(&s).unwrap();
+        assert_eq!("", rem);
+        assert_eq!(
+            vec![
+                MonkeyTest {
+                    divisible_by: 23,
+                    monkey_if_true: 2
+                },
+                MonkeyTest {
+                    divisible_by: 5,
+                    monkey_if_true: 1
+                }
+            ],
+            monkey.rule.tests
+        );
+        assert_eq!(3, monkey.rule.monkey_if_false);
+
+        // a test needs its own target, and there is always a target for none of them
+        assert!(MonkeyInput::parse(&s.replace("    If true: throw to monkey 2\n", "")).is_err());
+        assert!(MonkeyInput::parse(&s.replace("\n    If false: throw to monkey 3", "")).is_err());
+    }
+
     #[test]
     fn test_parse_op() {
+        use Expr::*;
+        use OpType::*;
+
+        assert_eq!(Ok(("", Expr::bin(Num(1), Add, Num(2)))), parse_op("1 + 2"));
+        assert_eq!(
+            Ok(("", Expr::bin(Old, Mul, Old))),
+            parse_op("old    *\told")
+        );
+        assert_eq!(Ok(("", Expr::bin(Old, Add, Num(3)))), parse_op("old+3"));
+        assert!(parse_op("foo + bar").is_err());
+    }
+
+    #[test]
+    fn test_parse_expr() {
+        use Expr::*;
         use OpType::*;
-        use OpVal::*;
 
-        assert_eq!(Ok(("", (Num(1), Add, Num(2)))), parse_op("1 + 2"));
-        assert_eq!(Ok(("", (Old, Mul, Old))), parse_op("old    *\told"));
-        assert_eq!(Ok(("", (Old, Add, Num(3)))), parse_op("old+3"));
-        assert!(matches!(parse_op("foo + bar"), Err(_)));
+        assert_eq!(
+            Ok(("", Expr::bin(Expr::bin(Old, Mul, Old), Add, Num(3)))),
+            parse_op("old * old + 3")
+        );
+        assert_eq!(
+            Ok(("", Expr::bin(Num(3), Add, Expr::bin(Old, Mul, Old)))),
+            parse_op("3 + old * old")
+        );
+        assert_eq!(
+            Ok(("", Expr::bin(Expr::bin(Old, Sub, Num(2)), Div, Num(5)))),
+            parse_op("(old - 2) / 5")
+        );
+        assert_eq!(
+            Ok(("", Expr::bin(Expr::bin(Old, Sub, Num(2)), Sub, Num(-1)))),
+            parse_op("old - 2 - -1")
+        );
+        assert_eq!(Ok(("", Old)), parse_op("( ( old ) )"));
+        // what is left is not an operator or an operand
+        assert_eq!(Ok((" +", Old)), parse_op("old +"));
+        assert!(parse_op("(old + 1").is_err());
+
+        for s in [
+            "old - (2 - old)",
+            "(old + 1) * (old - 1) / 7",
+            "old * 2 + -3",
+        ] {
+            assert_eq!(s, parse_op(s).unwrap().1.to_string());
+        }
     }
 
     #[test]
     fn parse_op_type() {
         assert_eq!(Ok(("", OpType::Add)), OpType::parse("+"));
+        assert_eq!(Ok(("", OpType::Sub)), OpType::parse("-"));
         assert_eq!(Ok(("", OpType::Mul)), OpType::parse("*"));
-        assert!(matches!(OpType::parse("foo"), Err(_)));
+        assert_eq!(Ok(("", OpType::Div)), OpType::parse("/"));
+        assert!(OpType::parse("foo").is_err());
     }
 
     #[test]
     fn parse_op_val() {
-        assert_eq!(Ok(("", OpVal::Old)), OpVal::parse_old("old"));
-        assert!(matches!(OpVal::parse_old("foo"), Err(_)));
-
-        assert_eq!(Ok(("", OpVal::Num(123))), OpVal::parse_num("123"));
-        assert_eq!(Ok(("", OpVal::Num(-456))), OpVal::parse_num("-456"));
-        assert_eq!(Ok(("", OpVal::Num(789))), OpVal::parse_num("+789"));
-        assert!(matches!(
-            OpVal::parse_num("123456789121232121433253553"),
-            Err(_)
-        )); // overflow
-        assert!(matches!(OpVal::parse_num("foo"), Err(_)));
-
-        assert_eq!(Ok(("", OpVal::Old)), OpVal::parse("old"));
-        assert_eq!(Ok(("", OpVal::Num(123))), OpVal::parse("123"));
+        assert_eq!(Ok(("", Expr::Old)), Expr::parse_old("old"));
+        assert!(Expr::parse_old("foo").is_err());
+
+        assert_eq!(Ok(("", Expr::Num(123))), Expr::parse_num("123"));
+        assert_eq!(Ok(("", Expr::Num(-456))), Expr::parse_num("-456"));
+        assert_eq!(Ok(("", Expr::Num(789))), Expr::parse_num("+789"));
+        assert!(Expr::parse_num("123456789121232121433253553").is_err()); // overflow
+        assert!(Expr::parse_num("foo").is_err());
+
+        assert_eq!(Ok(("", Expr::Old)), Expr::parse_factor("old"));
+        assert_eq!(Ok(("", Expr::Num(123))), Expr::parse_factor("123"));
     }
 }
@@ -1,46 +1,51 @@
-use crate::solution::{Solution, SolutionInput};
-use anyhow::{anyhow, ensure, Result};
-use itertools::Itertools;
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Debug,
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
 };
+use anyhow::{anyhow, ensure, Context, Result};
+use itertools::Itertools;
+use std::fmt::Debug;
 
+mod cycles;
+mod expr;
 mod input_parser;
+mod trace;
+mod worry;
 
-type Num = i64;
+pub use expr::{Expr, OpType};
+pub use trace::Inspection;
+pub use worry::Worry;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum OpVal {
-    Old,
-    Num(Num),
-}
+type Num = i64;
 
-impl OpVal {
-    fn resolve(&self, old: Num) -> Num {
-        match self {
-            Self::Old => old,
-            Self::Num(num) => *num,
-        }
-    }
-}
+type MonkeyName = usize;
 
+// a worry level divisible by the number sends the item to the monkey
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub enum OpType {
-    Add,
-    Mul,
+pub struct MonkeyTest {
+    divisible_by: Num,
+    monkey_if_true: MonkeyName,
 }
 
-type Op = (OpVal, OpType, OpVal);
-
-type MonkeyName = usize;
-
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct MonkeyRule {
-    op: Op,
-    divisible_by: Num,
-    monkey_if_true: MonkeyName,
-    monkey_if_false: MonkeyName,
+    op: Expr,
+    tests: Vec<MonkeyTest>, // tried in order, the first one passed decides
+    monkey_if_false: MonkeyName, // when none of the tests is passed
+}
+
+impl MonkeyRule {
+    // the index of the test passed, if any, and the monkey the item is thrown to
+    fn throw(&self, is_divisible_by: impl Fn(Num) -> bool) -> (Option<usize>, MonkeyName) {
+        match self
+            .tests
+            .iter()
+            .position(|test| is_divisible_by(test.divisible_by))
+        {
+            Some(idx) => (Some(idx), self.tests[idx].monkey_if_true),
+            None => (None, self.monkey_if_false),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -50,86 +55,185 @@ pub struct MonkeyInput {
     rule: MonkeyRule,
 }
 
-fn apply_op_to_item(item: &mut Num, (lhs, op, rhs): &Op) -> Result<()> {
-    let lhs = lhs.resolve(*item);
-    let rhs = rhs.resolve(*item);
-    *item = match op {
-        OpType::Add => lhs + rhs,
-        OpType::Mul => lhs * rhs,
-    };
+fn apply_op_to_item<W: Worry>(item: &mut W, op: &Expr) -> Result<()> {
+    *item = op.eval(item)?;
     Ok(())
 }
 
+// what a monkey does to the worry level after the op, before testing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relief {
+    DivideBy(Num), // the part 1 rule
+    Modulo(Num),   // keeps the numbers small without changing the test results
+    #[allow(dead_code)]
+    Unchanged, // the numbers grow fast, only for looking at them
+}
+
+impl Relief {
+    fn apply<W: Worry>(&self, item: W) -> W {
+        match self {
+            Self::DivideBy(d) => item.div_num(*d),
+            Self::Modulo(m) => W::from_num(item.rem_num(*m)),
+            Self::Unchanged => item,
+        }
+    }
+}
+
+// items are numbered in the order of the input, the id stays with the item
+pub type ItemId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Item<W> {
+    id: ItemId,
+    worry: W,
+}
+
 #[derive(Debug)]
-struct Monkey {
+struct Monkey<W> {
     _name: MonkeyName,
-    items: Vec<Num>,
+    items: Vec<Item<W>>,
     rule: MonkeyRule,
 }
 
 #[derive(Debug)]
-struct MonkeySet {
-    monkeys: Vec<Monkey>,
+struct MonkeySet<W> {
+    monkeys: Vec<Monkey<W>>,
+    round: usize, // the number of the rounds made
 }
 
-impl MonkeySet {
-    fn create(input: &[MonkeyInput]) -> MonkeySet {
+impl<W: Worry> MonkeySet<W> {
+    fn create(input: &[MonkeyInput]) -> MonkeySet<W> {
+        let mut ids = 0..;
         let monkeys = input
             .iter()
             .map(|monkey| Monkey {
                 _name: monkey.name,
-                items: monkey.starting_items.clone(),
+                items: monkey
+                    .starting_items
+                    .iter()
+                    .zip(ids.by_ref())
+                    .map(|(&worry, id)| Item {
+                        id,
+                        worry: W::from_num(worry),
+                    })
+                    .collect(),
                 rule: monkey.rule.clone(),
             })
             .collect_vec();
 
-        MonkeySet { monkeys }
+        MonkeySet { monkeys, round: 0 }
     }
 
-    fn make_turn<F: FnMut(&mut Num)>(
+    fn make_turn<F: FnMut(Inspection<W>)>(
         &mut self,
         name: usize,
-        inspection_count: &mut HashMap<MonkeyName, usize>,
-        after_op: &mut F,
+        relief: Relief,
+        on_inspection: &mut F,
     ) -> Result<()> {
+        let round = self.round + 1;
         let monkeys = &mut self.monkeys;
 
         let items = std::mem::take(&mut monkeys[name].items);
 
-        *inspection_count.entry(name).or_default() += items.len();
-
-        for mut item in items {
+        for item in items {
             let monkey = &monkeys[name];
             let rule = &monkey.rule;
             let op = &rule.op;
 
-            apply_op_to_item(&mut item, op)?;
-            after_op(&mut item);
-
-            let test_result = item % rule.divisible_by == 0;
-
-            let dst_monkey = if test_result {
-                rule.monkey_if_true
-            } else {
-                rule.monkey_if_false
-            };
-
-            monkeys[dst_monkey].items.push(item);
+            let mut worry = item.worry.clone();
+            apply_op_to_item(&mut worry, op).with_context(|| {
+                format!(
+                    "round {}, monkey {}, item {} with worry level {}",
+                    round, name, item.id, item.worry
+                )
+            })?;
+            let after_op = worry.clone();
+            let worry = relief.apply(worry);
+
+            let (passed_test, dst_monkey) = rule.throw(|d| worry.rem_num(d) == 0);
+
+            on_inspection(Inspection {
+                round,
+                monkey: name,
+                item: item.id,
+                before_op: item.worry,
+                after_op,
+                after_relief: worry.clone(),
+                passed_test,
+                target: dst_monkey,
+            });
+            monkeys[dst_monkey].items.push(Item { id: item.id, worry });
         }
 
         Ok(())
     }
 
-    fn make_round<F: FnMut(&mut Num)>(
+    fn make_round<F: FnMut(Inspection<W>)>(
         &mut self,
-        inspection_count: &mut HashMap<MonkeyName, usize>,
-        after_op: &mut F,
+        relief: Relief,
+        on_inspection: &mut F,
     ) -> Result<()> {
         for i in 0..self.monkeys.len() {
-            self.make_turn(i, inspection_count, after_op)?;
+            self.make_turn(i, relief, on_inspection)?;
         }
+        self.round += 1;
         Ok(())
     }
+
+    #[cfg(test)]
+    fn worry_levels(&self, name: MonkeyName) -> Vec<W> {
+        self.monkeys[name]
+            .items
+            .iter()
+            .map(|it| it.worry.clone())
+            .collect()
+    }
+}
+
+// every inspection of the given number of rounds, in order
+#[allow(dead_code)]
+pub fn trace<W: Worry>(
+    input: &[MonkeyInput],
+    rounds: usize,
+    relief: Relief,
+) -> Result<Vec<Inspection<W>>> {
+    let mut events = Vec::new();
+    let mut monkeys = MonkeySet::create(input);
+    for _ in 0..rounds {
+        monkeys.make_round(relief, &mut |event| events.push(event))?;
+    }
+    Ok(events)
+}
+
+// inspection counts by monkey, the worry levels are kept in W
+pub fn simulate_inspection_counts<W: Worry>(
+    input: &[MonkeyInput],
+    rounds: usize,
+    relief: Relief,
+) -> Result<Vec<usize>> {
+    let mut inspection_count = vec![0; input.len()];
+    let mut monkeys: MonkeySet<W> = MonkeySet::create(input);
+    for _ in 0..rounds {
+        monkeys.make_round(relief, &mut |event: Inspection<W>| {
+            inspection_count[event.monkey] += 1
+        })?
+    }
+    Ok(inspection_count)
+}
+
+// the product of the two largest inspection counts
+fn monkey_business_of(inspection_count: &[usize]) -> Result<usize> {
+    ensure!(
+        inspection_count.len() >= 2,
+        "at least two monkeys are needed, got {}",
+        inspection_count.len()
+    );
+    let counts = inspection_count.iter().sorted().rev().collect_vec();
+    Ok(counts[0] * counts[1])
+}
+
+fn monkey_business(input: &[MonkeyInput], rounds: usize, relief: Relief) -> Result<usize> {
+    monkey_business_of(&simulate_inspection_counts::<Num>(input, rounds, relief)?)
 }
 
 impl SolutionInput for Vec<MonkeyInput> {
@@ -150,27 +254,46 @@ impl Solution for Day11Pt1 {
     type TOutput = usize;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        let mut inspection_count: HashMap<MonkeyName, usize> = HashMap::new();
-        let mut monkeys: MonkeySet = MonkeySet::create(input);
-        for _ in 0..20 {
-            monkeys.make_round(&mut inspection_count, &mut |item: &mut Num| *item /= 3)?
-        }
-        let mut counts = inspection_count.iter().collect::<Vec<_>>();
-        counts.sort_by(|(_name_a, count_a), (_name_b, count_b)| usize::cmp(count_b, count_a));
-        Ok(counts[0].1 * counts[1].1)
+        monkey_business(input, 20, Relief::DivideBy(3))
     }
 }
 
-fn find_divisor(input: &[MonkeyInput]) -> Num {
-    let distinct_divisors = input
-        .iter()
-        .map(|monkey| monkey.rule.divisible_by)
-        .collect::<HashSet<_>>()
+fn gcd(a: Num, b: Num) -> Num {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// the least common multiple of all the divisibility tests, None if it overflows
+fn find_divisor(input: &[MonkeyInput]) -> Option<Num> {
+    input
         .iter()
-        .cloned()
-        .collect_vec();
+        .flat_map(|monkey| monkey.rule.tests.iter().map(|test| test.divisible_by))
+        .try_fold(1, |lcm: Num, divisor| {
+            (lcm / gcd(lcm, divisor)).checked_mul(divisor)
+        })
+}
 
-    distinct_divisors.iter().product()
+// the divisor to keep the worry levels modulo, if all the operations allow it
+fn modular_divisor(input: &[MonkeyInput]) -> Result<Num> {
+    let divisor = find_divisor(input).ok_or_else(|| {
+        anyhow!(
+            "the divisibility tests have no common multiple that fits in {}",
+            Num::MAX
+        )
+    })?;
+    for monkey in input.iter() {
+        ensure!(
+            monkey.rule.op.is_modular(),
+            "monkey {}: new = {} divides the worry level, so it can't be kept modulo {}",
+            monkey.name,
+            monkey.rule.op,
+            divisor
+        );
+    }
+    Ok(divisor)
 }
 
 pub struct Day11Pt2;
@@ -183,24 +306,22 @@ impl Solution for Day11Pt2 {
     type TOutput = usize;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        let mut inspection_count: HashMap<MonkeyName, usize> = HashMap::new();
-        let mut monkeys: MonkeySet = MonkeySet::create(input);
-        let divisor = find_divisor(input);
-        for _ in 0..10000 {
-            monkeys.make_round(&mut inspection_count, &mut |item| *item %= divisor)?
-        }
-        let mut counts = inspection_count.iter().collect::<Vec<_>>();
-        counts.sort_by(|(_name_a, count_a), (_name_b, count_b)| usize::cmp(count_b, count_a));
-        Ok(counts[0].1 * counts[1].1)
+        monkey_business(input, 10000, Relief::Modulo(modular_divisor(input)?))
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day11Pt1>();
+    registry.add::<Day11Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use crate::util::get_input;
     use lazy_static::lazy_static;
+    use num_bigint::BigInt;
 
     lazy_static! {
         static ref INPUT_TEST: Vec<MonkeyInput> = get_input::<Day11Pt1>("test.txt").unwrap();
@@ -231,14 +352,139 @@ mod tests {
         Ok(())
     }
 
+    fn test_input_with_op(monkey: MonkeyName, op: &str) -> Result<Vec<MonkeyInput>> {
+        let mut input = INPUT_TEST.clone();
+        input[monkey].rule.op = input_parser::parse_op(op)
+            .map_err(|e| anyhow!("{:?}", e))?
+            .1;
+        Ok(input)
+    }
+
+    // monkey 0 of the example throws the items divisible by 5 to monkey 1 if not by 23
+    fn test_input_with_second_test() -> Vec<MonkeyInput> {
+        let mut input = INPUT_TEST.clone();
+        input[0].rule.tests.push(MonkeyTest {
+            divisible_by: 5,
+            monkey_if_true: 1,
+        });
+        input
+    }
+
+    #[test]
+    fn test_several_tests() -> Result<()> {
+        let rule = &test_input_with_second_test()[0].rule;
+        assert_eq!((Some(0), 2), rule.throw(|d| 115 % d == 0));
+        assert_eq!((Some(1), 1), rule.throw(|d| 10 % d == 0));
+        assert_eq!((None, 3), rule.throw(|d| 7 % d == 0));
+
+        let input = test_input_with_second_test();
+        // 5 is a new divisor for the modulo
+        assert_eq!(23 * 19 * 13 * 17 * 5, modular_divisor(&input)?);
+        let events = trace::<Num>(&input, 1, Relief::DivideBy(3))?;
+        // 79 * 19 / 3 = 500, not divisible by 23, but by 5
+        assert_eq!((Some(1), 1), (events[0].passed_test, events[0].target));
+        assert_ne!(Day11Pt2::solve(&INPUT_TEST)?, Day11Pt2::solve(&input)?);
+        assert_eq!(
+            simulate_inspection_counts::<Num>(&input, 20, Relief::DivideBy(3))?,
+            simulate_inspection_counts::<BigInt>(&input, 20, Relief::DivideBy(3))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modular_divisor() -> Result<()> {
+        assert_eq!(23 * 19 * 13 * 17, modular_divisor(&INPUT_TEST)?);
+
+        let input = test_input_with_op(2, "(old - 2) / 5")?;
+        let err = modular_divisor(&input).unwrap_err();
+        assert_eq!(
+            "monkey 2: new = (old - 2) / 5 divides the worry level, so it can't be kept modulo 96577",
+            err.to_string()
+        );
+        assert!(Day11Pt2::solve(&input).is_err());
+        // part 1 is fine with it
+        assert!(Day11Pt1::solve(&input).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_modular_divisor_overflow() -> Result<()> {
+        let mut input = INPUT_TEST.clone();
+        // shared factors count once
+        input[0].rule.tests[0].divisible_by = 23 * 19;
+        assert_eq!(23 * 19 * 13 * 17, modular_divisor(&input)?);
+
+        input[0].rule.tests[0].divisible_by = 1_000_000_007;
+        input[1].rule.tests[0].divisible_by = 998_244_353;
+        input[2].rule.tests[0].divisible_by = 1_000_000_009;
+        let err = modular_divisor(&input).unwrap_err();
+        assert_eq!(
+            "the divisibility tests have no common multiple that fits in 9223372036854775807",
+            err.to_string()
+        );
+        assert!(Day11Pt2::solve(&input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_modulo_with_subtraction() -> Result<()> {
+        // the worry levels can go below zero, the modulo keeps the same test results
+        let input = test_input_with_op(1, "old - 100")?;
+        let divisor = modular_divisor(&input)?;
+        let targets =
+            |events: Vec<Inspection>| events.iter().map(|it| (it.item, it.target)).collect_vec();
+        let exact = trace(&input, 3, Relief::DivideBy(1))?;
+        assert!(exact.iter().any(|it| it.after_op < 0));
+        assert_eq!(
+            targets(exact),
+            targets(trace(&input, 3, Relief::Modulo(divisor))?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_overflow() {
+        let err =
+            simulate_inspection_counts::<Num>(&INPUT_TEST, 20, Relief::Unchanged).unwrap_err();
+        assert_eq!(
+            "round 11, monkey 2, item 0 with worry level 3740147530: \
+             overflow in old * old with old = 3740147530",
+            format!("{:#}", err)
+        );
+    }
+
+    #[test]
+    fn test_big_int() -> Result<()> {
+        // the exact worry levels go to the same monkeys as the ones modulo the divisor
+        let rounds = 12;
+        assert_eq!(
+            simulate_inspection_counts::<Num>(
+                &INPUT_TEST,
+                rounds,
+                Relief::Modulo(modular_divisor(&INPUT_TEST)?)
+            )?,
+            simulate_inspection_counts::<BigInt>(&INPUT_TEST, rounds, Relief::Unchanged)?
+        );
+        let events = trace::<BigInt>(&INPUT_TEST, rounds, Relief::Unchanged)?;
+        let largest = events.iter().map(|it| &it.after_relief).max().unwrap();
+        // beyond i64
+        assert!(largest.bits() > 63);
+
+        assert_eq!(
+            simulate_inspection_counts::<Num>(&INPUT_MAIN, 20, Relief::DivideBy(3))?,
+            simulate_inspection_counts::<BigInt>(&INPUT_MAIN, 20, Relief::DivideBy(3))?
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_make_round() -> Result<()> {
-        let mut m: MonkeySet = MonkeySet::create(&INPUT_TEST);
-        m.make_round(&mut HashMap::new(), &mut |item: &mut Num| *item /= 3)?;
-        assert_eq!(m.monkeys[0].items, vec![20, 23, 27, 26]);
-        assert_eq!(m.monkeys[1].items, vec![2080, 25, 167, 207, 401, 1046]);
-        assert_eq!(m.monkeys[2].items, vec![]);
-        assert_eq!(m.monkeys[3].items, vec![]);
+        let mut m: MonkeySet<Num> = MonkeySet::create(&INPUT_TEST);
+        m.make_round(Relief::DivideBy(3), &mut |_| ())?;
+        assert_eq!(m.worry_levels(0), vec![20, 23, 27, 26]);
+        assert_eq!(m.worry_levels(1), vec![2080, 25, 167, 207, 401, 1046]);
+        assert_eq!(m.worry_levels(2), Vec::<Num>::new());
+        assert_eq!(m.worry_levels(3), Vec::<Num>::new());
         Ok(())
     }
 }
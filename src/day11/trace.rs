@@ -0,0 +1,268 @@
+use std::fmt::Display;
+
+use super::{Expr, ItemId, MonkeyInput, MonkeyName, Num, OpType, Relief};
+
+// a monkey looking at an item during its turn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection<W = Num> {
+    pub round: usize, // starting from 1
+    pub monkey: MonkeyName,
+    pub item: ItemId,
+    pub before_op: W,
+    pub after_op: W,
+    pub after_relief: W,            // what is tested and thrown
+    pub passed_test: Option<usize>, // the index of the test passed in the rule
+    pub target: MonkeyName,
+}
+
+// the inspections of one item, the monkeys it visited are the ones inspecting it
+#[allow(dead_code)]
+pub fn journey<W: Clone>(events: &[Inspection<W>], item: ItemId) -> Vec<Inspection<W>> {
+    events
+        .iter()
+        .filter(|event| event.item == item)
+        .cloned()
+        .collect()
+}
+
+fn describe_op<W: Display>(input: &MonkeyInput, after_op: &W) -> String {
+    let op = &input.rule.op;
+    let Expr::BinOp(lhs, op_type, rhs) = op else {
+        return format!("Worry level is set to {}.", after_op);
+    };
+    let verb = match op_type {
+        OpType::Add => "increases by",
+        OpType::Sub => "decreases by",
+        OpType::Mul => "is multiplied by",
+        OpType::Div => "is divided by",
+    };
+    let commutative = matches!(op_type, OpType::Add | OpType::Mul);
+    match (lhs.as_ref(), rhs.as_ref()) {
+        (Expr::Old, Expr::Old) => format!("Worry level {} itself to {}.", verb, after_op),
+        (Expr::Old, Expr::Num(num)) => format!("Worry level {} {} to {}.", verb, num, after_op),
+        (Expr::Num(num), Expr::Old) if commutative => {
+            format!("Worry level {} {} to {}.", verb, num, after_op)
+        }
+        _ => format!("Worry level becomes {} = {}.", op, after_op),
+    }
+}
+
+fn describe_relief<W: Display>(relief: Relief, after_relief: &W) -> Option<String> {
+    match relief {
+        Relief::DivideBy(d) => Some(format!(
+            "Monkey gets bored with item. Worry level is divided by {} to {}.",
+            d, after_relief
+        )),
+        Relief::Modulo(m) => Some(format!(
+            "Worry level is reduced modulo {} to {}.",
+            m, after_relief
+        )),
+        Relief::Unchanged => None,
+    }
+}
+
+// the verbose description of the rounds from the puzzle, the rounds are separated by empty lines
+#[allow(dead_code)]
+pub fn narrate<W: Display>(
+    input: &[MonkeyInput],
+    events: &[Inspection<W>],
+    relief: Relief,
+) -> String {
+    let mut lines = Vec::new();
+    let mut turn = None;
+    for event in events.iter() {
+        let rule = &input[event.monkey].rule;
+        if turn != Some((event.round, event.monkey)) {
+            if turn.is_some_and(|(round, _)| round != event.round) {
+                lines.push(String::new());
+            }
+            lines.push(format!("Monkey {}:", event.monkey));
+            turn = Some((event.round, event.monkey));
+        }
+        lines.push(format!(
+            "  Monkey inspects an item with a worry level of {}.",
+            event.before_op
+        ));
+        lines.push(format!(
+            "    {}",
+            describe_op(&input[event.monkey], &event.after_op)
+        ));
+        if let Some(relief) = describe_relief(relief, &event.after_relief) {
+            lines.push(format!("    {}", relief));
+        }
+        // the tests are tried till one is passed
+        for (idx, test) in rule.tests.iter().enumerate() {
+            let passed = event.passed_test == Some(idx);
+            lines.push(format!(
+                "    Current worry level is {}divisible by {}.",
+                if passed { "" } else { "not " },
+                test.divisible_by
+            ));
+            if passed {
+                break;
+            }
+        }
+        lines.push(format!(
+            "    Item with worry level {} is thrown to monkey {}.",
+            event.after_relief, event.target
+        ));
+    }
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{
+        day11::{trace, Day11Pt1, MonkeyTest},
+        util::get_input,
+    };
+    use anyhow::Result;
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref INPUT_TEST: Vec<MonkeyInput> = get_input::<Day11Pt1>("test.txt").unwrap();
+    }
+
+    #[test]
+    fn test_narrate() -> Result<()> {
+        let events = trace::<Num>(&INPUT_TEST, 1, Relief::DivideBy(3))?;
+        let expected = "\
+Monkey 0:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by 19 to 1501.
+    Monkey gets bored with item. Worry level is divided by 3 to 500.
+    Current worry level is not divisible by 23.
+    Item with worry level 500 is thrown to monkey 3.
+  Monkey inspects an item with a worry level of 98.
+    Worry level is multiplied by 19 to 1862.
+    Monkey gets bored with item. Worry level is divided by 3 to 620.
+    Current worry level is not divisible by 23.
+    Item with worry level 620 is thrown to monkey 3.
+Monkey 1:
+  Monkey inspects an item with a worry level of 54.
+    Worry level increases by 6 to 60.
+    Monkey gets bored with item. Worry level is divided by 3 to 20.
+    Current worry level is not divisible by 19.
+    Item with worry level 20 is thrown to monkey 0.
+  Monkey inspects an item with a worry level of 65.
+    Worry level increases by 6 to 71.
+    Monkey gets bored with item. Worry level is divided by 3 to 23.
+    Current worry level is not divisible by 19.
+    Item with worry level 23 is thrown to monkey 0.
+  Monkey inspects an item with a worry level of 75.
+    Worry level increases by 6 to 81.
+    Monkey gets bored with item. Worry level is divided by 3 to 27.
+    Current worry level is not divisible by 19.
+    Item with worry level 27 is thrown to monkey 0.
+  Monkey inspects an item with a worry level of 74.
+    Worry level increases by 6 to 80.
+    Monkey gets bored with item. Worry level is divided by 3 to 26.
+    Current worry level is not divisible by 19.
+    Item with worry level 26 is thrown to monkey 0.
+Monkey 2:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by itself to 6241.
+    Monkey gets bored with item. Worry level is divided by 3 to 2080.
+    Current worry level is divisible by 13.
+    Item with worry level 2080 is thrown to monkey 1.
+  Monkey inspects an item with a worry level of 60.
+    Worry level is multiplied by itself to 3600.
+    Monkey gets bored with item. Worry level is divided by 3 to 1200.
+    Current worry level is not divisible by 13.
+    Item with worry level 1200 is thrown to monkey 3.
+  Monkey inspects an item with a worry level of 97.
+    Worry level is multiplied by itself to 9409.
+    Monkey gets bored with item. Worry level is divided by 3 to 3136.
+    Current worry level is not divisible by 13.
+    Item with worry level 3136 is thrown to monkey 3.
+Monkey 3:
+  Monkey inspects an item with a worry level of 74.
+    Worry level increases by 3 to 77.
+    Monkey gets bored with item. Worry level is divided by 3 to 25.
+    Current worry level is not divisible by 17.
+    Item with worry level 25 is thrown to monkey 1.
+  Monkey inspects an item with a worry level of 500.
+    Worry level increases by 3 to 503.
+    Monkey gets bored with item. Worry level is divided by 3 to 167.
+    Current worry level is not divisible by 17.
+    Item with worry level 167 is thrown to monkey 1.
+  Monkey inspects an item with a worry level of 620.
+    Worry level increases by 3 to 623.
+    Monkey gets bored with item. Worry level is divided by 3 to 207.
+    Current worry level is not divisible by 17.
+    Item with worry level 207 is thrown to monkey 1.
+  Monkey inspects an item with a worry level of 1200.
+    Worry level increases by 3 to 1203.
+    Monkey gets bored with item. Worry level is divided by 3 to 401.
+    Current worry level is not divisible by 17.
+    Item with worry level 401 is thrown to monkey 1.
+  Monkey inspects an item with a worry level of 3136.
+    Worry level increases by 3 to 3139.
+    Monkey gets bored with item. Worry level is divided by 3 to 1046.
+    Current worry level is not divisible by 17.
+    Item with worry level 1046 is thrown to monkey 1.
+";
+        assert_eq!(expected, narrate(&INPUT_TEST, &events, Relief::DivideBy(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_narrate_several_tests() -> Result<()> {
+        let mut input = INPUT_TEST.clone();
+        input[0].rule.tests.push(MonkeyTest {
+            divisible_by: 5,
+            monkey_if_true: 1,
+        });
+        let events = trace::<Num>(&input, 1, Relief::DivideBy(3))?;
+        let expected = "\
+Monkey 0:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by 19 to 1501.
+    Monkey gets bored with item. Worry level is divided by 3 to 500.
+    Current worry level is not divisible by 23.
+    Current worry level is divisible by 5.
+    Item with worry level 500 is thrown to monkey 1.
+  Monkey inspects an item with a worry level of 98.
+    Worry level is multiplied by 19 to 1862.
+    Monkey gets bored with item. Worry level is divided by 3 to 620.
+    Current worry level is not divisible by 23.
+    Current worry level is divisible by 5.
+    Item with worry level 620 is thrown to monkey 1.
+";
+        let narration = narrate(&input, &events[..2], Relief::DivideBy(3));
+        assert_eq!(expected, narration);
+        Ok(())
+    }
+
+    #[test]
+    fn test_journey() -> Result<()> {
+        let events = trace(&INPUT_TEST, 2, Relief::DivideBy(3))?;
+        // 79 of monkey 0 ends up with monkey 1 after both of the rounds
+        let visited = journey(&events, 0)
+            .iter()
+            .map(|it| (it.round, it.monkey, it.after_relief, it.target))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (1, 0, 500, 3),
+                (1, 3, 167, 1),
+                (2, 1, 57, 2),
+                (2, 2, 1083, 3),
+                (2, 3, 362, 1)
+            ],
+            visited
+        );
+
+        // every item is thrown to where it is inspected next
+        for id in 0..10 {
+            let steps = journey(&events, id);
+            assert!(steps
+                .windows(2)
+                .all(|w| w[0].target == w[1].monkey && w[0].after_relief == w[1].before_op));
+        }
+        assert_eq!(0, journey(&events, 10).len());
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+use std::fmt::{Debug, Display};
+
+use super::{Num, OpType};
+
+// the numbers the worry levels are kept in: i64 reports overflows,
+// BigInt never overflows but gets slow when the numbers grow
+pub trait Worry: Clone + Debug + Display + PartialEq + Zero {
+    fn from_num(num: Num) -> Self;
+
+    // None if the result does not fit
+    fn checked_op(&self, op: OpType, rhs: &Self) -> Option<Self>;
+
+    fn div_num(&self, d: Num) -> Self;
+
+    // the euclidean remainder, from 0 to m - 1
+    fn rem_num(&self, m: Num) -> Num;
+}
+
+impl Worry for Num {
+    fn from_num(num: Num) -> Self {
+        num
+    }
+
+    fn checked_op(&self, op: OpType, rhs: &Self) -> Option<Self> {
+        match op {
+            OpType::Add => self.checked_add(*rhs),
+            OpType::Sub => self.checked_sub(*rhs),
+            OpType::Mul => self.checked_mul(*rhs),
+            OpType::Div => self.checked_div(*rhs),
+        }
+    }
+
+    fn div_num(&self, d: Num) -> Self {
+        self / d
+    }
+
+    fn rem_num(&self, m: Num) -> Num {
+        self.rem_euclid(m)
+    }
+}
+
+impl Worry for BigInt {
+    fn from_num(num: Num) -> Self {
+        BigInt::from(num)
+    }
+
+    fn checked_op(&self, op: OpType, rhs: &Self) -> Option<Self> {
+        match op {
+            OpType::Add => Some(self + rhs),
+            OpType::Sub => Some(self - rhs),
+            OpType::Mul => Some(self * rhs),
+            // rounds towards zero, the same as i64
+            OpType::Div => (!rhs.is_zero()).then(|| self / rhs),
+        }
+    }
+
+    fn div_num(&self, d: Num) -> Self {
+        self / d
+    }
+
+    fn rem_num(&self, m: Num) -> Num {
+        let rem = self % m;
+        let rem = if rem.is_negative() { rem + m } else { rem };
+        // less than m, so it fits
+        rem.to_i64().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_backends_agree() {
+        for (a, b) in [(7, 3), (-7, 3), (7, -3), (-7, -3), (0, 5)] {
+            for op in [OpType::Add, OpType::Sub, OpType::Mul, OpType::Div] {
+                let big = BigInt::from_num(a).checked_op(op, &BigInt::from_num(b));
+                assert_eq!(
+                    big,
+                    a.checked_op(op, &b).map(BigInt::from),
+                    "{} {:?} {}",
+                    a,
+                    op,
+                    b
+                );
+            }
+            assert_eq!(BigInt::from_num(a).div_num(2), BigInt::from(a.div_num(2)));
+            assert_eq!(BigInt::from_num(a).rem_num(5), a.rem_num(5));
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(None, Num::MAX.checked_op(OpType::Mul, &2));
+        assert_eq!(None, 1.checked_op(OpType::Div, &0));
+        assert_eq!(
+            None,
+            BigInt::from(1).checked_op(OpType::Div, &BigInt::zero())
+        );
+
+        let big = BigInt::from_num(Num::MAX).checked_op(OpType::Mul, &BigInt::from(2));
+        assert_eq!(
+            Some("18446744073709551614".to_string()),
+            big.map(|it| it.to_string())
+        );
+    }
+}
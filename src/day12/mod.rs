@@ -1,56 +1,81 @@
-use std::collections::{HashSet, VecDeque};
-
 use anyhow::{anyhow, bail, ensure, Result};
+use itertools::Itertools;
+use std::collections::HashMap;
 
 use crate::{
+    registry::Registry,
+    search::{astar, bfs_all_shortest, SearchSpace, ShortestPaths, Visit},
     solution::{Solution, SolutionInput},
-    util::Vec2d,
+    util::{Grid, Vec2d},
 };
 
 type Point = (isize, isize);
 
 type Map = Vec2d<i8, isize>;
 
-impl Map {
-    fn at(&self, pt: &Point) -> Option<i8> {
-        self.get(pt).cloned()
-    }
+fn can_climb(height: i8, next_height: i8) -> bool {
+    next_height <= height + 1
+}
 
-    fn find_path<EndPred, PathPred>(
+impl Map {
+    // shortest route from the start to the end, both included; a* heads for the end,
+    // which can't be fewer steps away than the manhattan distance
+    fn find_path<PathPred>(
         &self,
         start: Point,
-        end_pred: EndPred,
+        end: Point,
         path_pred: PathPred,
-    ) -> Result<usize>
+    ) -> Result<Vec<Point>>
     where
-        EndPred: Fn(Point) -> bool,
         PathPred: Fn(i8, i8) -> bool,
     {
-        let mut queue = VecDeque::new();
-        let mut visited = HashSet::new();
-
-        let start_height = self.at(&start).ok_or_else(|| anyhow!("start out of map"))?;
-        queue.push_back((start, start_height, 0));
-        visited.insert(start);
+        ensure!(self.is_inside(&start), "start out of map");
 
-        while let Some((pt, height, path_len)) = queue.pop_front() {
-            if end_pred(pt) {
-                return Ok(path_len);
+        let climb = self.filter_edges(|&from, &to| path_pred(self[from], self[to]));
+        let distance = |&(x, y): &Point| end.0.abs_diff(x) + end.1.abs_diff(y);
+        let result = astar(&climb, [start], distance, |&pt, _| {
+            if pt == end {
+                Visit::Stop
+            } else {
+                Visit::Continue
             }
+        });
 
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let next_pt = (pt.0 + dx, pt.1 + dy);
+        result.goal_path().ok_or_else(|| {
+            let reached = result.reached().map(|(&pt, _)| (pt, '*')).collect();
+            anyhow!(
+                "path not found from {:?}, reachable points are marked with '*':\n{}",
+                start,
+                self.draw_marked(&reached)
+            )
+        })
+    }
 
-                if let Some(next_pt_height) = self.at(&next_pt) {
-                    if path_pred(height, next_pt_height) && !visited.contains(&next_pt) {
-                        queue.push_back((next_pt, next_pt_height, path_len + 1));
-                        visited.insert(next_pt);
-                    }
-                }
-            }
-        }
+    fn draw_marked(&self, marks: &HashMap<Point, char>) -> String {
+        self.display_with(|pt, &height| match marks.get(&pt) {
+            Some(&mark) => mark,
+            None => (b'a' + height as u8) as char,
+        })
+        .to_string()
+    }
 
-        bail!("path not found");
+    // the heightmap with arrows pointing along the route, 'E' at its end
+    pub fn draw_route(&self, route: &[Point]) -> String {
+        let mut marks = HashMap::new();
+        for (from, to) in route.iter().tuple_windows() {
+            let arrow = match (to.0 - from.0, to.1 - from.1) {
+                (0, -1) => '^',
+                (0, 1) => 'v',
+                (-1, 0) => '<',
+                (1, 0) => '>',
+                _ => '?',
+            };
+            marks.insert(*from, arrow);
+        }
+        if let Some(&end) = route.last() {
+            marks.insert(end, 'E');
+        }
+        self.draw_marked(&marks)
     }
 }
 
@@ -87,6 +112,79 @@ impl SolutionInput for Input {
     }
 }
 
+impl Input {
+    pub fn route(&self) -> Result<Vec<Point>> {
+        self.map.find_path(self.start, self.end, can_climb)
+    }
+
+    // going down from the end reaches every point by all its shortest routes
+    fn descend_from_end(&self) -> ShortestPaths<Point> {
+        let descend = self
+            .map
+            .filter_edges(|&from, &to| can_climb(self.map[to], self.map[from]));
+        bfs_all_shortest(&descend, [self.end])
+    }
+
+    // the lowest points the end is the fewest steps away from, ordered, and the steps
+    fn best_lowest(&self, paths: &ShortestPaths<Point>) -> Result<(Vec<Point>, usize)> {
+        let lowest = paths
+            .reached()
+            .filter(|&(&pt, _)| self.map[pt] == 0)
+            .collect_vec();
+        let best_steps = lowest
+            .iter()
+            .map(|&(_, steps)| steps)
+            .min()
+            .ok_or_else(|| anyhow!("the end is not reachable from any lowest point"))?;
+        let starts = lowest
+            .into_iter()
+            .filter(|&(_, steps)| steps == best_steps)
+            .map(|(&pt, _)| pt)
+            .sorted()
+            .collect();
+        Ok((starts, best_steps))
+    }
+
+    pub fn fewest_steps_from_lowest(&self) -> Result<usize> {
+        let (_, steps) = self.best_lowest(&self.descend_from_end())?;
+        Ok(steps)
+    }
+
+    // the shortest routes to the end from any of the lowest points, at most `limit` of them,
+    // ordered; the lowest points are taken in order, the routes of each in no particular one
+    pub fn best_routes_from_lowest(&self, limit: usize) -> Result<Vec<Vec<Point>>> {
+        let paths = self.descend_from_end();
+        let (starts, _) = self.best_lowest(&paths)?;
+        let mut routes = Vec::new();
+        for pt in starts.iter() {
+            routes.extend(paths.paths_to(pt, limit - routes.len()));
+        }
+        for route in routes.iter_mut() {
+            route.reverse();
+        }
+        routes.sort();
+        Ok(routes)
+    }
+}
+
+// the most routes `aoc2022 map 12 --routes` draws, the real inputs have more than a u128 holds
+pub const MAX_DRAWN_ROUTES: usize = 10;
+
+// the heightmap for `aoc2022 map 12` with the route from the start, or with the shortest
+// routes from the lowest points, one map after another
+pub fn draw_routes(input_str: &str, from_lowest: bool) -> Result<String> {
+    let input = Input::parse(input_str)?;
+    let routes = if from_lowest {
+        input.best_routes_from_lowest(MAX_DRAWN_ROUTES)?
+    } else {
+        vec![input.route()?]
+    };
+    Ok(routes
+        .iter()
+        .map(|route| input.map.draw_route(route))
+        .join("\n\n"))
+}
+
 pub struct Day12Pt1;
 
 impl Solution for Day12Pt1 {
@@ -97,11 +195,7 @@ impl Solution for Day12Pt1 {
     type TOutput = usize;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        input.map.find_path(
-            input.start,
-            |pt| pt == input.end,
-            |height, next_height| next_height <= height + 1,
-        )
+        Ok(input.route()?.len() - 1)
     }
 }
 
@@ -115,19 +209,20 @@ impl Solution for Day12Pt2 {
     type TOutput = usize;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        input.map.find_path(
-            input.end,
-            |pt| input.map.at(&pt) == Some(0),
-            |height, next_height| height <= next_height + 1,
-        )
+        input.fewest_steps_from_lowest()
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day12Pt1>();
+    registry.add::<Day12Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::util::get_input;
+    use crate::util::{get_input, read_input};
     use lazy_static::lazy_static;
 
     lazy_static! {
@@ -158,4 +253,81 @@ mod tests {
         assert_eq!(31, Day12Pt1::solve(&INPUT_TEST)?);
         Ok(())
     }
+
+    #[test]
+    fn test_route() -> Result<()> {
+        let input: &Input = &INPUT_TEST;
+        let route = input.route()?;
+        assert_eq!(Some(&input.start), route.first());
+        assert_eq!(Some(&input.end), route.last());
+        // as long as the one in the puzzle statement, ties are broken differently
+        let expected = "\
+>>vv<<<<
+abvvv<<^
+acvv>E^^
+acv>>>^^
+ab>>>>>^";
+        assert_eq!(expected, input.map.draw_route(&route));
+        Ok(())
+    }
+
+    #[test]
+    fn test_best_routes_from_lowest() -> Result<()> {
+        let input: &Input = &INPUT_TEST;
+        let routes = input.best_routes_from_lowest(usize::MAX)?;
+        assert_eq!(vec![(0, 4)], routes.iter().map(|it| it[0]).collect_vec());
+        assert_eq!(30, routes[0].len());
+        assert_eq!(Some(&input.end), routes[0].last());
+
+        // two lowest points 25 steps away, each with two ways into the middle row
+        let input = Input::parse(
+            &["Sb", "bcdefghijklmnopqrstuvwxyE", "ab"]
+                .map(|row| format!("{:z<25}", row))
+                .join("\n"),
+        )?;
+        let middle_row = (2..25).map(|x| (x, 1)).collect_vec();
+        let expected = [
+            [(0, 0), (0, 1), (1, 1)],
+            [(0, 0), (1, 0), (1, 1)],
+            [(0, 2), (0, 1), (1, 1)],
+            [(0, 2), (1, 2), (1, 1)],
+        ]
+        .map(|start| [&start[..], &middle_row].concat());
+        assert_eq!(
+            expected.to_vec(),
+            input.best_routes_from_lowest(usize::MAX)?
+        );
+        assert_eq!(expected[..3].to_vec(), input.best_routes_from_lowest(3)?);
+        assert_eq!(25, input.fewest_steps_from_lowest()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_routes() -> Result<()> {
+        let input_str = read_input(12, "test.txt")?;
+        assert_eq!(
+            INPUT_TEST.map.draw_route(&INPUT_TEST.route()?),
+            draw_routes(&input_str, false)?
+        );
+        let maps = draw_routes(&input_str, true)?;
+        // all of them from the lowest point at (0, 4)
+        for map in maps.split("\n\n") {
+            let row = map.lines().nth(4).unwrap();
+            assert!(row.starts_with(['^', '>']), "{}", map);
+        }
+
+        let input_str = read_input(12, "input.txt")?;
+        let maps = draw_routes(&input_str, true)?;
+        assert_eq!(MAX_DRAWN_ROUTES, maps.split("\n\n").count());
+        assert!(draw_routes("Sbz\nazE", true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_not_found() -> Result<()> {
+        let input = Input::parse("Sbz\nazE")?;
+        let err = input.route().unwrap_err().to_string();
+        assert!(err.contains("**z\n*zz"), "{}", err);
+        Ok(())
+    }
 }
@@ -1,4 +1,7 @@
-use crate::solution::{Solution, SolutionInput};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+};
 use anyhow::{anyhow, Result};
 use itertools::{EitherOrBoth, Itertools};
 use std::{cmp::Ordering, fmt::Display};
@@ -146,6 +149,11 @@ impl Solution for Day13Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day13Pt1>();
+    registry.add::<Day13Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,60 +1,29 @@
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{ensure, Result};
 use itertools::Itertools;
+use std::fmt::{self, Display};
 
-use crate::util::Vec2d;
+use crate::util::{ChunkedGrid, Grid};
 
 use super::{Coord, Line, Point};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum MapPoint {
+    #[default]
     Empty,
     Wall,
     Sand,
 }
 
-impl Default for MapPoint {
-    fn default() -> Self {
-        MapPoint::Empty
-    }
+#[derive(Debug, Clone)]
+pub struct Map {
+    grid: ChunkedGrid<MapPoint, Coord>,
+    lowest_wall: Coord,
+    floor: Option<Coord>,
 }
 
-pub type Map = Vec2d<MapPoint, Coord>;
-
 impl Map {
-    pub fn create(lines: &[Line], spawn_pos: &Point, floor_offset: Option<Coord>) -> Result<Map> {
-        fn include_pt(min: &mut Point, max: &mut Point, &(x, y): &Point) {
-            min.0 = min.0.min(x);
-            max.0 = max.0.max(x);
-            min.1 = min.1.min(y);
-            max.1 = max.1.max(y);
-        }
-
-        let mut min = *spawn_pos;
-        let mut max = *spawn_pos;
-
-        lines
-            .iter()
-            .flat_map(|line| line.iter())
-            .for_each(|pt| include_pt(&mut min, &mut max, pt));
-
-        if let Some(off) = floor_offset {
-            max.1 += off;
-        }
-
-        // sand cannot go more horizonally then verically
-        let max_fall_height = max.1 - spawn_pos.1;
-        include_pt(
-            &mut min,
-            &mut max,
-            &(spawn_pos.0 - max_fall_height, spawn_pos.1 + max_fall_height),
-        );
-        include_pt(
-            &mut min,
-            &mut max,
-            &(spawn_pos.0 + max_fall_height, spawn_pos.1 + max_fall_height),
-        );
-
-        let mut map = Vec2d::new(min, max, MapPoint::Empty)?;
+    pub fn create(lines: &[Line], floor_offset: Option<Coord>) -> Result<Map> {
+        let mut grid = ChunkedGrid::new(MapPoint::Empty);
 
         for line in lines {
             for (&(x0, y0), &(x1, y1)) in line.iter().tuple_windows::<(_, _)>() {
@@ -67,7 +36,7 @@ impl Map {
                 );
                 let mut pt = (x0, y0);
                 loop {
-                    map.set(&pt, MapPoint::Wall)?;
+                    grid[pt] = MapPoint::Wall;
                     if pt == (x1, y1) {
                         break;
                     }
@@ -77,29 +46,42 @@ impl Map {
             }
         }
 
-        if floor_offset.is_some() {
-            for x in min.0..=max.0 {
-                map.set(&(x, max.1), MapPoint::Wall)?;
-            }
-        }
+        let lowest_wall = match grid.bounds() {
+            Some((_, max)) => max.1,
+            None => 0,
+        };
+        Ok(Map {
+            grid,
+            lowest_wall,
+            floor: floor_offset.map(|off| lowest_wall + off),
+        })
+    }
 
-        Ok(map)
+    // there is nothing to stop the sand below this
+    pub fn bottom(&self) -> Coord {
+        self.floor.unwrap_or(self.lowest_wall)
     }
 
-    pub fn at(&self, pt: &Point) -> Result<MapPoint> {
-        self.get(pt)
-            .cloned()
-            .ok_or_else(|| anyhow!("out of map {:?}, {:?}-{:?}", pt, self.min, self.max))
+    pub fn at(&self, pt: &Point) -> MapPoint {
+        if Some(pt.1) == self.floor {
+            MapPoint::Wall
+        } else {
+            self.grid[*pt]
+        }
     }
 
-    pub fn set(&mut self, pt: &Point, v: MapPoint) -> Result<()> {
-        let min = self.min;
-        let max = self.max;
-        let map_pt = self
-            .get_mut(pt)
-            .ok_or_else(|| anyhow!("out of map {:?}, {:?}-{:?}", pt, min, max))?;
-        *map_pt = v;
+    pub fn set(&mut self, pt: &Point, v: MapPoint) {
+        self.grid[*pt] = v;
+    }
+}
 
-        Ok(())
+impl Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let picture = self.grid.display_with(|_pt, &map_pt| match map_pt {
+            MapPoint::Empty => '.',
+            MapPoint::Wall => '#',
+            MapPoint::Sand => 'o',
+        });
+        write!(f, "{}", picture)
     }
 }
@@ -4,9 +4,10 @@ mod parser;
 use self::parser::parse_lines;
 use crate::{
     day14::map::MapPoint,
+    registry::Registry,
     solution::{Solution, SolutionInput},
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::Result;
 use map::Map;
 
 type Coord = i32;
@@ -19,37 +20,30 @@ impl SolutionInput for Vec<Line> {
     }
 }
 
-fn add_sand(map: &mut Map, spawn_pos: Point) -> Result<bool> {
-    fn next_pos(map: &Map, pos: &Point) -> Result<Option<Point>> {
-        for d in [(0, 1), (-1, 1), (1, 1)] {
-            let next_pos = (pos.0 + d.0, pos.1 + d.1);
-            if map
-                .at(&next_pos)
-                .with_context(|| anyhow!("next_pos for {:?}", pos))?
-                == MapPoint::Empty
-            {
-                return Ok(Some(next_pos));
-            }
-        }
-        Ok(None)
+fn add_sand(map: &mut Map, spawn_pos: Point) -> bool {
+    fn next_pos(map: &Map, pos: &Point) -> Option<Point> {
+        [(0, 1), (-1, 1), (1, 1)]
+            .into_iter()
+            .map(|d| (pos.0 + d.0, pos.1 + d.1))
+            .find(|next_pos| map.at(next_pos) == MapPoint::Empty)
     }
 
-    if map.at(&spawn_pos)? != MapPoint::Empty {
-        return Ok(false);
+    if map.at(&spawn_pos) != MapPoint::Empty {
+        return false;
     }
 
     let mut pos = spawn_pos;
-    let max_y = map.max.1;
-    while pos.1 < max_y {
-        match next_pos(map, &pos)? {
+    let bottom = map.bottom();
+    while pos.1 < bottom {
+        match next_pos(map, &pos) {
             Some(next_pos) => pos = next_pos,
             None => {
-                map.set(&pos, MapPoint::Sand)?;
-                return Ok(true);
+                map.set(&pos, MapPoint::Sand);
+                return true;
             }
         }
     }
-    Ok(false)
+    false
 }
 
 pub struct Day14Pt1;
@@ -61,10 +55,9 @@ impl Solution for Day14Pt1 {
     type TOutput = usize;
 
     fn solve(lines: &Vec<Line>) -> Result<Self::TOutput> {
-        let spawn_pos = (500, 0);
-        let mut map: Map = Map::create(lines, &spawn_pos, None)?;
+        let mut map: Map = Map::create(lines, None)?;
         let mut count = 0;
-        while add_sand(&mut map, spawn_pos)? {
+        while add_sand(&mut map, (500, 0)) {
             count += 1;
         }
         Ok(count)
@@ -80,16 +73,20 @@ impl Solution for Day14Pt2 {
     type TOutput = usize;
 
     fn solve(lines: &Vec<Line>) -> Result<Self::TOutput> {
-        let spawn_pos = (500, 0);
-        let mut map: Map = Map::create(lines, &spawn_pos, Some(2))?;
+        let mut map: Map = Map::create(lines, Some(2))?;
         let mut count = 0;
-        while add_sand(&mut map, (500, 0))? {
+        while add_sand(&mut map, (500, 0)) {
             count += 1;
         }
         Ok(count)
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day14Pt1>();
+    registry.add::<Day14Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -129,25 +126,31 @@ mod tests {
 
     #[test]
     fn test_add_sand() -> Result<()> {
-        let mut map: Map = Map::create(&INPUT_TEST, &(500, 0), None)?;
-        assert!(add_sand(&mut map, (500, 0))?);
-        assert_eq!(MapPoint::Sand, map.at(&(500, 8))?);
-        assert!(add_sand(&mut map, (500, 0))?);
-        assert_eq!(MapPoint::Sand, map.at(&(499, 8))?);
-        assert!(add_sand(&mut map, (500, 0))?);
-        assert_eq!(MapPoint::Sand, map.at(&(501, 8))?);
-        assert!(add_sand(&mut map, (500, 0))?);
-        assert_eq!(MapPoint::Sand, map.at(&(500, 7))?);
+        let mut map: Map = Map::create(&INPUT_TEST, None)?;
+        assert!(add_sand(&mut map, (500, 0)));
+        assert_eq!(MapPoint::Sand, map.at(&(500, 8)));
+        assert!(add_sand(&mut map, (500, 0)));
+        assert_eq!(MapPoint::Sand, map.at(&(499, 8)));
+        assert!(add_sand(&mut map, (500, 0)));
+        assert_eq!(MapPoint::Sand, map.at(&(501, 8)));
+        assert!(add_sand(&mut map, (500, 0)));
+        assert_eq!(MapPoint::Sand, map.at(&(500, 7)));
         Ok(())
     }
 
     #[test]
     fn test_parse() -> Result<()> {
-        let map: Map = Map::create(&INPUT_TEST, &(500, 0), None)?;
-        assert_eq!(map.at(&(503, 4))?, MapPoint::Wall);
-        assert_eq!(map.at(&(502, 4))?, MapPoint::Wall);
-        assert_eq!(map.at(&(501, 4))?, MapPoint::Empty);
-        assert_eq!(map.max.1, 9);
+        let map: Map = Map::create(&INPUT_TEST, None)?;
+        assert_eq!(map.at(&(503, 4)), MapPoint::Wall);
+        assert_eq!(map.at(&(502, 4)), MapPoint::Wall);
+        assert_eq!(map.at(&(501, 4)), MapPoint::Empty);
+        assert_eq!(map.bottom(), 9);
+
+        let picture = map.to_string();
+        let rows = picture.lines().collect::<Vec<_>>();
+        assert_eq!(6, rows.len());
+        assert_eq!("....#...##", rows[0]);
+        assert_eq!("#########.", rows[5]);
 
         Ok(())
     }
@@ -0,0 +1,137 @@
+use itertools::Itertools;
+
+use super::{find_free, Coord, FreeSearch, LineRestrictions, Point, Sensor};
+
+// the sensors with queries about the area they cover
+#[derive(Debug, Clone)]
+pub struct SensorField {
+    sensors: Vec<Sensor>,
+}
+
+impl SensorField {
+    pub fn new(sensors: Vec<Sensor>) -> SensorField {
+        SensorField { sensors }
+    }
+
+    pub fn sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    // sensors and beacons are always inside of the covered area
+    fn known_points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.sensors
+            .iter()
+            .flat_map(|sensor| [sensor.pos, sensor.beacon])
+            .unique()
+    }
+
+    // the union of the sensor areas on the row, sorted ranges of x without overlaps
+    pub fn covered_intervals(&self, y: Coord) -> Vec<(Coord, Coord)> {
+        LineRestrictions::new(&self.sensors, y).covered()
+    }
+
+    // the number of cells on the row where there can't be a beacon,
+    // sensors and beacons themselves are not counted
+    pub fn coverage_on_row(&self, y: Coord) -> Coord {
+        LineRestrictions::new(&self.sensors, y).count_restricted()
+    }
+
+    // the same, for all the rows of the rectangle, min and max included
+    pub fn coverage_in_rect(&self, min: Point, max: Point) -> Coord {
+        let covered: Coord = (min.1..=max.1)
+            .flat_map(|y| self.covered_intervals(y))
+            .map(|(x0, x1)| (x1.min(max.0) - x0.max(min.0) + 1).max(0))
+            .sum();
+        let known = self
+            .known_points()
+            .filter(|&(x, y)| x >= min.0 && x <= max.0 && y >= min.1 && y <= max.1)
+            .count();
+        covered - known as Coord
+    }
+
+    // all the cells of the rectangle not covered by any sensor, row by row
+    pub fn free_cells(&self, min: Point, max: Point) -> Vec<Point> {
+        let mut result = Vec::new();
+        for y in min.1..=max.1 {
+            let mut x = min.0;
+            for (x0, x1) in self.covered_intervals(y) {
+                result.extend((x..=(x0 - 1).min(max.0)).map(|x| (x, y)));
+                x = x.max(x1 + 1);
+            }
+            result.extend((x..=max.0).map(|x| (x, y)));
+        }
+        result
+    }
+
+    // some free cell in the rectangle, meant for the ones with only one of them
+    pub fn find_free(&self, min: Point, max: Point, method: FreeSearch) -> Option<Point> {
+        find_free(&self.sensors, min, max, method)
+    }
+}
+
+pub fn tuning_frequency((x, y): Point) -> Coord {
+    x * 4000000 + y
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{day15::Day15Pt1, util::get_input};
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref INPUT_TEST: SensorField = get_input::<Day15Pt1>("test.txt").unwrap();
+    }
+
+    #[test]
+    fn test_rows() {
+        let field: &SensorField = &INPUT_TEST;
+        assert_eq!(26, field.coverage_on_row(10));
+        assert_eq!(vec![(-2, 24)], field.covered_intervals(10));
+        assert_eq!(vec![(-3, 13), (15, 25)], field.covered_intervals(11));
+        assert_eq!(Vec::<(Coord, Coord)>::new(), field.covered_intervals(-11));
+    }
+
+    #[test]
+    fn test_rect() {
+        let field: &SensorField = &INPUT_TEST;
+        assert_eq!(
+            field.coverage_on_row(10),
+            field.coverage_in_rect((-100, 10), (100, 10))
+        );
+
+        // against checking every cell
+        let (min, max) = ((-5, -5), (25, 25));
+        let known = field.known_points().collect_vec();
+        let naive = (min.1..=max.1)
+            .cartesian_product(min.0..=max.0)
+            .map(|(y, x)| (x, y))
+            .filter(|pt| !known.contains(pt))
+            .filter(|&pt| {
+                field.sensors().iter().any(|sensor| {
+                    sensor
+                        .get_restricted_x(pt.1)
+                        .is_some_and(|(x0, x1)| x0 <= pt.0 && pt.0 <= x1)
+                })
+            })
+            .count();
+        assert_eq!(naive as Coord, field.coverage_in_rect(min, max));
+    }
+
+    #[test]
+    fn test_free() {
+        let field: &SensorField = &INPUT_TEST;
+        assert_eq!(vec![(14, 11)], field.free_cells((0, 0), (20, 20)));
+        assert_eq!(
+            vec![(-4, 11), (-4, 12), (-3, 12)],
+            field.free_cells((-4, 11), (-3, 12))
+        );
+        assert_eq!(
+            Some(56000011),
+            field
+                .find_free((0, 0), (20, 20), FreeSearch::LineIntersections)
+                .map(tuning_frequency)
+        );
+    }
+}
@@ -1,11 +1,18 @@
+mod field;
 mod parser;
+mod render;
 
-use self::parser::parse_sensors;
-use crate::solution::{Solution, SolutionInput};
+use self::{parser::parse_sensors, render::Viewport};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+    util::IntervalSet,
+};
+pub use field::{tuning_frequency, SensorField};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Error, Result};
 use itertools::Itertools;
-use std::{cmp::Ordering, collections::HashSet};
+use std::{collections::HashSet, env, str::FromStr};
 
 type Coord = i64;
 type Point = (Coord, Coord);
@@ -43,103 +50,40 @@ impl Sensor {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum PtType {
-    // order is important, Start should be before End, with Beacon and Sensor between them
-    Start,
-    Beacon,
-    Sensor,
-    End,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Pt {
-    typ: PtType,
-    x: Coord,
-}
-
-impl Pt {
-    fn new(typ: PtType, x: Coord) -> Pt {
-        Pt { typ, x }
-    }
-}
-
-impl PartialOrd for Pt {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl Ord for Pt {
-    fn cmp(&self, other: &Self) -> Ordering {
-        use Ordering::*;
-
-        match self.x.cmp(&other.x) {
-            cmp @ (Greater | Less) => cmp,
-            Equal => self.typ.cmp(&other.typ),
-        }
-    }
-}
-
 #[derive(Debug)]
 struct LineRestrictions {
-    vec: Vec<Pt>, // sorted by coord
+    covered: IntervalSet<Coord>, // by the sensor areas
+    known: HashSet<Coord>,       // where a beacon or a sensor is situated
 }
 
 impl LineRestrictions {
     fn new(sensors: &[Sensor], at_y: Coord) -> LineRestrictions {
-        use PtType::*;
-
-        // points where beacon or sensor is situated
-        let mut pts = HashSet::new();
-        for sensor in sensors.iter() {
-            for (typ, (x, y)) in [(Sensor, sensor.pos), (Beacon, sensor.beacon)] {
-                if y == at_y {
-                    pts.insert(Pt::new(typ, x));
-                }
-            }
-        }
-
-        let mut vec = sensors
+        let covered = sensors
             .iter()
             .filter_map(|sensor| sensor.get_restricted_x(at_y))
-            .flat_map(|(start, end)| [Pt::new(Start, start), Pt::new(End, end)].into_iter())
-            .chain(pts.into_iter())
-            .collect_vec();
-
-        vec.sort();
+            .collect();
+        let known = sensors
+            .iter()
+            .flat_map(|sensor| [sensor.pos, sensor.beacon])
+            .filter(|&(_, y)| y == at_y)
+            .map(|(x, _)| x)
+            .collect();
 
-        LineRestrictions { vec }
+        LineRestrictions { covered, known }
     }
 
     fn count_restricted(&self) -> Coord {
-        let mut result = 0;
-        let mut start_count = 0;
-        let mut start_x = 0;
-
-        for pt in self.vec.iter() {
-            match pt.typ {
-                PtType::Start => {
-                    if start_count == 0 {
-                        start_x = pt.x;
-                    }
-                    start_count += 1;
-                }
-                PtType::Beacon | PtType::Sensor => {
-                    if start_count > 0 {
-                        result -= 1;
-                    }
-                }
-                PtType::End => {
-                    start_count -= 1;
-                    if start_count == 0 {
-                        let len = pt.x - start_x + 1;
-                        result += len;
-                    }
-                }
-            }
-        }
+        let known = self
+            .known
+            .iter()
+            .filter(|&&x| self.covered.contains(x))
+            .count();
+        (self.covered.len() - known as u128) as Coord
+    }
 
-        result
+    // the covered ranges of x, sorted, without overlapping or touching ones
+    fn covered(&self) -> Vec<(Coord, Coord)> {
+        self.covered.intervals().to_vec()
     }
 }
 
@@ -153,20 +97,60 @@ fn is_point_outside((x, y): Point, sensors: &[Sensor]) -> bool {
     true
 }
 
-fn find_free(sensors: &[Sensor], min: Point, max: Point) -> Option<Point> {
+fn is_inside_box((x, y): Point, min: Point, max: Point) -> bool {
+    x >= min.0 && x <= max.0 && y >= min.1 && y <= max.1
+}
+
+// picks the part 2 algorithm: "perimeter" or "lines" (the default)
+pub const FREE_SEARCH_VAR: &str = "AOC_DAY15_FREE_SEARCH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreeSearch {
+    // check every point just outside of every sensor area
+    Perimeter,
+    // check only the points where the borders of the sensor areas cross
+    LineIntersections,
+}
+
+impl FromStr for FreeSearch {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<FreeSearch> {
+        Ok(match s {
+            "perimeter" => FreeSearch::Perimeter,
+            "lines" => FreeSearch::LineIntersections,
+            _ => bail!(
+                "unexpected free search {:?}, expected perimeter or lines",
+                s
+            ),
+        })
+    }
+}
+
+impl FreeSearch {
+    pub fn from_env() -> Result<FreeSearch> {
+        match env::var(FREE_SEARCH_VAR) {
+            Ok(s) => s.parse().with_context(|| anyhow!("in {}", FREE_SEARCH_VAR)),
+            Err(_) => Ok(FreeSearch::LineIntersections),
+        }
+    }
+}
+
+fn find_free(sensors: &[Sensor], min: Point, max: Point, method: FreeSearch) -> Option<Point> {
+    match method {
+        FreeSearch::Perimeter => find_free_on_perimeters(sensors, min, max),
+        FreeSearch::LineIntersections => find_free_on_intersections(sensors, min, max),
+    }
+}
+
+fn find_free_on_perimeters(sensors: &[Sensor], min: Point, max: Point) -> Option<Point> {
     for sensor in sensors.iter() {
         for a in 0..=sensor.distance + 1 {
             let b = sensor.distance + 1 - a;
             for (dx, dy) in [(a, b), (a, -b), (-a, b), (-a, -b)] {
-                let x = sensor.pos.0 + dx;
-                let y = sensor.pos.1 + dy;
-                if x >= min.0
-                    && x <= max.0
-                    && y >= min.1
-                    && y <= max.1
-                    && is_point_outside((x, y), sensors)
-                {
-                    return Some((x, y));
+                let pt = (sensor.pos.0 + dx, sensor.pos.1 + dy);
+                if is_inside_box(pt, min, max) && is_point_outside(pt, sensors) {
+                    return Some(pt);
                 }
             }
         }
@@ -174,9 +158,101 @@ fn find_free(sensors: &[Sensor], min: Point, max: Point) -> Option<Point> {
     None
 }
 
-impl SolutionInput for Vec<Sensor> {
+// the free point is boxed in by the sensor areas, or by the edges of the search box,
+// so it lies where the lines just outside of the areas cross each other or the edges
+fn find_free_on_intersections(sensors: &[Sensor], min: Point, max: Point) -> Option<Point> {
+    // lines y = x + c and y = -x + c, by c
+    let mut rising = HashSet::new();
+    let mut falling = HashSet::new();
+    for sensor in sensors.iter() {
+        let (x, y) = sensor.pos;
+        let r = sensor.distance + 1;
+        rising.extend([y - x - r, y - x + r]);
+        falling.extend([y + x - r, y + x + r]);
+    }
+    // the edges of the box as such lines, through its corners
+    rising.extend([min.1 - min.0, min.1 - max.0, max.1 - min.0, max.1 - max.0]);
+    falling.extend([min.1 + min.0, min.1 + max.0, max.1 + min.0, max.1 + max.0]);
+
+    let crossings = rising
+        .iter()
+        .cartesian_product(falling.iter())
+        .filter(|&(a, b)| (b - a) % 2 == 0)
+        .map(|(a, b)| ((b - a) / 2, (a + b) / 2));
+
+    let edges = rising
+        .iter()
+        .flat_map(|&a| {
+            [
+                (min.0, min.0 + a),
+                (max.0, max.0 + a),
+                (min.1 - a, min.1),
+                (max.1 - a, max.1),
+            ]
+        })
+        .chain(falling.iter().flat_map(|&b| {
+            [
+                (min.0, b - min.0),
+                (max.0, b - max.0),
+                (b - min.1, min.1),
+                (b - max.1, max.1),
+            ]
+        }));
+
+    crossings
+        .chain(edges)
+        .find(|&pt| is_inside_box(pt, min, max) && is_point_outside(pt, sensors))
+}
+
+impl SolutionInput for SensorField {
     fn parse(input_str: &str) -> Result<Self> {
-        parse_sensors(input_str)
+        Ok(SensorField::new(parse_sensors(input_str)?))
+    }
+}
+
+// the viewport asked for, or the one around all the sensors and beacons
+fn viewport(sensors: &[Sensor], view: Option<(Point, Point)>) -> Result<Viewport> {
+    match view {
+        Some((min, max)) => Viewport::new(min, max),
+        None => Viewport::around(sensors),
+    }
+}
+
+// the text map for `aoc2022 map 15`, with the areas of all the sensors, or only of the one
+// at the given point
+pub fn draw_map(
+    input_str: &str,
+    view: Option<(Point, Point)>,
+    sensor: Option<Point>,
+) -> Result<String> {
+    let field = SensorField::parse(input_str)?;
+    let sensors = field.sensors();
+    let viewport = viewport(sensors, view)?;
+    match sensor {
+        Some(pos) => {
+            let area = sensors
+                .iter()
+                .find(|it| it.pos == pos)
+                .ok_or_else(|| anyhow!("no sensor at {:?}", pos))?;
+            viewport.render_areas(sensors, &SensorField::new(vec![area.clone()]))
+        }
+        None => viewport.render(&field),
+    }
+}
+
+// the image for `aoc2022 map 15 --image`, PGM or, in color, PPM
+pub fn draw_image(
+    input_str: &str,
+    view: Option<(Point, Point)>,
+    (width, height): (usize, usize),
+    color: bool,
+) -> Result<Vec<u8>> {
+    let field = SensorField::parse(input_str)?;
+    let viewport = viewport(field.sensors(), view)?;
+    if color {
+        viewport.render_ppm(&field, width, height)
+    } else {
+        viewport.render_pgm(&field, width, height)
     }
 }
 
@@ -185,12 +261,11 @@ impl Solution for Day15Pt1 {
     const DAY: usize = 15;
     const PART: usize = 1;
 
-    type TInput = Vec<Sensor>;
+    type TInput = SensorField;
     type TOutput = Coord;
 
-    fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        let restriction = LineRestrictions::new(input, 2000000);
-        Ok(restriction.count_restricted())
+    fn solve(field: &Self::TInput) -> Result<Self::TOutput> {
+        Ok(field.coverage_on_row(2000000))
     }
 }
 
@@ -199,27 +274,32 @@ impl Solution for Day15Pt2 {
     const DAY: usize = 15;
     const PART: usize = 2;
 
-    type TInput = Vec<Sensor>;
+    type TInput = SensorField;
     type TOutput = Coord;
 
-    fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        let pt = find_free(input, (0, 0), (4000000, 4000000))
+    fn solve(field: &Self::TInput) -> Result<Self::TOutput> {
+        let pt = field
+            .find_free((0, 0), (4000000, 4000000), FreeSearch::from_env()?)
             .ok_or_else(|| anyhow!("free space not found"))?;
-        Ok(pt.0 * 4000000 + pt.1)
+        Ok(tuning_frequency(pt))
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day15Pt1>();
+    registry.add::<Day15Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use crate::util::get_input;
-    use all_asserts::assert_lt;
     use lazy_static::lazy_static;
 
     lazy_static! {
-        static ref INPUT_TEST: Vec<Sensor> = get_input::<Day15Pt1>("test.txt").unwrap();
-        static ref INPUT_MAIN: Vec<Sensor> = get_input::<Day15Pt1>("input.txt").unwrap();
+        static ref INPUT_TEST: SensorField = get_input::<Day15Pt1>("test.txt").unwrap();
+        static ref INPUT_MAIN: SensorField = get_input::<Day15Pt1>("input.txt").unwrap();
     }
 
     #[test]
@@ -236,7 +316,52 @@ mod tests {
 
     #[test]
     fn test_find_free() {
-        assert_eq!(find_free(&INPUT_TEST, (0, 0), (20, 20)), Some((14, 11)));
+        for method in [FreeSearch::Perimeter, FreeSearch::LineIntersections] {
+            assert_eq!(
+                INPUT_TEST.find_free((0, 0), (20, 20), method),
+                Some((14, 11))
+            );
+        }
+    }
+
+    #[test]
+    fn test_free_search_from_str() -> Result<()> {
+        assert_eq!(FreeSearch::Perimeter, "perimeter".parse()?);
+        assert_eq!(FreeSearch::LineIntersections, "lines".parse()?);
+        assert!("diagonals".parse::<FreeSearch>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_free_methods_agree() {
+        let main = ((0, 0), (4000000, 4000000));
+        let boxes = [
+            ((0, 0), (20, 20)),
+            ((10, 8), (14, 11)),  // free point at the corner
+            ((14, 11), (18, 11)), // and on the edge
+            ((0, 0), (5, 5)),     // nothing free
+            ((-3, -3), (3, 3)),
+            ((20, 0), (30, 20)),
+        ];
+        for (field, (min, max)) in boxes
+            .iter()
+            .map(|&it| (&*INPUT_TEST, it))
+            .chain([(&*INPUT_MAIN, main)])
+        {
+            let by_perimeter = field.find_free(min, max, FreeSearch::Perimeter);
+            let by_lines = field.find_free(min, max, FreeSearch::LineIntersections);
+            assert_eq!(
+                by_perimeter.is_some(),
+                by_lines.is_some(),
+                "{:?}-{:?}",
+                min,
+                max
+            );
+            for pt in [by_perimeter, by_lines].into_iter().flatten() {
+                assert!(is_inside_box(pt, min, max));
+                assert_eq!(vec![pt], field.free_cells(pt, pt));
+            }
+        }
     }
 
     #[test]
@@ -248,58 +373,60 @@ mod tests {
         10 ..####B######################..
         11 .###S#############.###########.
         */
-        let r_9 = LineRestrictions::new(&INPUT_TEST, 9);
-        assert_eq!(r_9.count_restricted(), 25);
-
-        let r_10 = LineRestrictions::new(&INPUT_TEST, 10);
-        assert_eq!(r_10.count_restricted(), 26);
-
-        let r_11 = LineRestrictions::new(&INPUT_TEST, 11);
-        assert_eq!(r_11.count_restricted(), 27);
+        assert_eq!(25, INPUT_TEST.coverage_on_row(9));
+        assert_eq!(vec![(-1, 23)], INPUT_TEST.covered_intervals(9));
+
+        assert_eq!(26, INPUT_TEST.coverage_on_row(10));
+        assert_eq!(vec![(-2, 24)], INPUT_TEST.covered_intervals(10));
+
+        assert_eq!(27, INPUT_TEST.coverage_on_row(11));
+        assert_eq!(vec![(-3, 13), (15, 25)], INPUT_TEST.covered_intervals(11));
+        assert_eq!(
+            25 + 26 + 27,
+            INPUT_TEST.coverage_in_rect((-100, 9), (100, 11))
+        );
     }
 
     #[test]
-    fn test_pt_order() {
-        assert_lt!(PtType::Start, PtType::Beacon);
-        assert_lt!(PtType::Start, PtType::Sensor);
-        assert_lt!(PtType::Start, PtType::End);
-        assert_lt!(PtType::Beacon, PtType::End);
-        assert_lt!(PtType::Sensor, PtType::End);
-    }
-
-    #[test]
-    fn test_get_restricted_x() {
-        /*
-                       1    1    2    2
-             0    5    0    5    0    5
-        -2 ..........#.................
-        -1 .........###................
-         0 ....S...#####...............
-         1 .......#######........S.....
-         2 ......#########S............
-         3 .....###########SB..........
-         4 ....#############...........
-         5 ...###############..........
-         6 ..#################.........
-         7 .#########S#######S#........
-         8 ..#################.........
-         9 ...###############..........
-        10 ....B############...........
-        11 ..S..###########............
-        12 ......#########.............
-        13 .......#######..............
-        14 ........#####.S.......S.....
-        15 B........###................
-        16 ..........#SB...............
-        17 ................S..........B
-        */
-
+    fn test_get_restricted_x() -> Result<()> {
         let sensor = Sensor::new((8, 7), (2, 10));
+        let map = "
+               1    1    2    2
+     0    5    0    5    0    5
+-2 ..........#.................
+-1 .........###................
+ 0 ....S...#####...............
+ 1 .......#######........S.....
+ 2 ......#########S............
+ 3 .....###########SB..........
+ 4 ....#############...........
+ 5 ...###############..........
+ 6 ..#################.........
+ 7 .#########S#######S#........
+ 8 ..#################.........
+ 9 ...###############..........
+10 ....B############...........
+11 ..S..###########............
+12 ......#########.............
+13 .......#######..............
+14 ........#####.S.......S.....
+15 B........###................
+16 ..........#SB...............
+17 ................S..........B";
+        assert_eq!(
+            &map[1..],
+            Viewport::new((-2, -2), (25, 17))?.render_areas(
+                INPUT_TEST.sensors(),
+                &SensorField::new(vec![sensor.clone()])
+            )?
+        );
+
         assert_eq!(sensor.get_restricted_x(7), Some((-1, 17)));
         assert_eq!(sensor.get_restricted_x(10), Some((2, 14)));
         assert_eq!(sensor.get_restricted_x(16), Some((8, 8)));
         assert_eq!(sensor.get_restricted_x(-2), Some((8, 8)));
         assert_eq!(sensor.get_restricted_x(17), None);
         assert_eq!(sensor.get_restricted_x(-3), None);
+        Ok(())
     }
 }
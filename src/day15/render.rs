@@ -0,0 +1,311 @@
+use anyhow::{ensure, Result};
+use std::collections::{HashMap, HashSet};
+
+use super::{Coord, Point, Sensor, SensorField};
+
+// the text maps get too wide to look at beyond that, images are for the larger ones
+const MAX_MAP_SIZE: Coord = 500;
+
+// the part of the field to draw, min and max included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    min: Point,
+    max: Point,
+}
+
+fn markers(sensors: &[Sensor]) -> HashMap<Point, char> {
+    let mut result = HashMap::new();
+    for sensor in sensors.iter() {
+        result.insert(sensor.beacon, 'B');
+    }
+    // a sensor is never at the beacon of another one, but just in case
+    for sensor in sensors.iter() {
+        result.insert(sensor.pos, 'S');
+    }
+    result
+}
+
+// the column numbers are written top down, aligned at the bottom
+fn header(min_x: Coord, max_x: Coord, indent: usize) -> Vec<String> {
+    let labels = (min_x..=max_x)
+        .map(|x| {
+            if x % 5 == 0 {
+                x.to_string()
+            } else {
+                String::new()
+            }
+        })
+        .collect::<Vec<_>>();
+    let height = labels.iter().map(|it| it.len()).max().unwrap_or(0);
+    (0..height)
+        .map(|line| {
+            let chars = labels.iter().map(|label| {
+                let skip = height - label.len();
+                if line < skip {
+                    ' '
+                } else {
+                    label.as_bytes()[line - skip] as char
+                }
+            });
+            " ".repeat(indent)
+                .chars()
+                .chain(chars)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+impl Viewport {
+    pub fn new(min: Point, max: Point) -> Result<Viewport> {
+        ensure!(
+            min.0 <= max.0 && min.1 <= max.1,
+            "empty viewport {:?}-{:?}",
+            min,
+            max
+        );
+        Ok(Viewport { min, max })
+    }
+
+    // the smallest one with all the sensors and beacons
+    pub fn around(sensors: &[Sensor]) -> Result<Viewport> {
+        let points = || {
+            sensors
+                .iter()
+                .flat_map(|sensor| [sensor.pos, sensor.beacon])
+        };
+        ensure!(!sensors.is_empty(), "no sensors to draw");
+        let min = (
+            points().map(|pt| pt.0).min().unwrap(),
+            points().map(|pt| pt.1).min().unwrap(),
+        );
+        let max = (
+            points().map(|pt| pt.0).max().unwrap(),
+            points().map(|pt| pt.1).max().unwrap(),
+        );
+        Viewport::new(min, max)
+    }
+
+    fn width(&self) -> Coord {
+        self.max.0 - self.min.0 + 1
+    }
+
+    fn height(&self) -> Coord {
+        self.max.1 - self.min.1 + 1
+    }
+
+    // the map as in the puzzle description, with the areas of all the sensors
+    pub fn render(&self, field: &SensorField) -> Result<String> {
+        self.render_areas(field.sensors(), field)
+    }
+
+    // all the sensors and beacons are drawn, but only the areas of the sensors of the field
+    pub fn render_areas(&self, sensors: &[Sensor], areas: &SensorField) -> Result<String> {
+        ensure!(
+            self.width() <= MAX_MAP_SIZE && self.height() <= MAX_MAP_SIZE,
+            "{:?}-{:?} is too large for a map, at most {} cells across are drawn",
+            self.min,
+            self.max,
+            MAX_MAP_SIZE
+        );
+        let markers = markers(sensors);
+        let free = areas
+            .free_cells(self.min, self.max)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let label_width = (self.min.1..=self.max.1)
+            .map(|y| y.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = header(self.min.0, self.max.0, label_width + 1);
+        for y in self.min.1..=self.max.1 {
+            let row = (self.min.0..=self.max.0)
+                .map(|x| match markers.get(&(x, y)) {
+                    Some(&marker) => marker,
+                    None if free.contains(&(x, y)) => '.',
+                    None => '#',
+                })
+                .collect::<String>();
+            lines.push(format!("{:>w$} {}", y, row, w = label_width));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    // one byte per pixel, 0 if there can't be a beacon in any cell of the pixel, 255 if there
+    // can be one in all of them; only the middle row of the cells of each pixel is looked at
+    fn coverage_levels(&self, field: &SensorField, width: usize, height: usize) -> Result<Vec<u8>> {
+        ensure!(width > 0 && height > 0, "empty image {}x{}", width, height);
+        ensure!(
+            width as Coord <= self.width() && height as Coord <= self.height(),
+            "image {}x{} is larger than the viewport {:?}-{:?}",
+            width,
+            height,
+            self.min,
+            self.max
+        );
+
+        let cell_x = |i: usize| self.min.0 + i as Coord * self.width() / width as Coord;
+        let cell_y = |j: usize| self.min.1 + j as Coord * self.height() / height as Coord;
+
+        let mut result = Vec::with_capacity(width * height);
+        for j in 0..height {
+            let y = (cell_y(j) + cell_y(j + 1) - 1) / 2;
+            for i in 0..width {
+                let pixel = (cell_x(i), cell_x(i + 1) - 1);
+                let covered = field.coverage_in_rect((pixel.0, y), (pixel.1, y));
+                let len = pixel.1 - pixel.0 + 1;
+                result.push((255 - covered * 255 / len) as u8);
+            }
+        }
+        Ok(result)
+    }
+
+    fn pixel_of(&self, (x, y): Point, width: usize, height: usize) -> Option<usize> {
+        if x < self.min.0 || x > self.max.0 || y < self.min.1 || y > self.max.1 {
+            return None;
+        }
+        let i = ((x - self.min.0) * width as Coord / self.width()) as usize;
+        let j = ((y - self.min.1) * height as Coord / self.height()) as usize;
+        Some(j * width + i)
+    }
+
+    // binary grayscale image of the coverage, scaled down to width x height pixels
+    pub fn render_pgm(&self, field: &SensorField, width: usize, height: usize) -> Result<Vec<u8>> {
+        let mut result = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+        result.extend(self.coverage_levels(field, width, height)?);
+        Ok(result)
+    }
+
+    // the same in color, with the sensors in red and the beacons in blue
+    pub fn render_ppm(&self, field: &SensorField, width: usize, height: usize) -> Result<Vec<u8>> {
+        let mut pixels = self
+            .coverage_levels(field, width, height)?
+            .into_iter()
+            .map(|level| [level; 3])
+            .collect::<Vec<_>>();
+        for (pt, marker) in markers(field.sensors()) {
+            if let Some(pixel) = self.pixel_of(pt, width, height) {
+                pixels[pixel] = if marker == 'S' {
+                    [255, 0, 0]
+                } else {
+                    [0, 0, 255]
+                };
+            }
+        }
+
+        let mut result = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        result.extend(pixels.into_iter().flatten());
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{
+        day15::{draw_image, draw_map, Day15Pt1, SensorField},
+        util::{get_input, read_input},
+    };
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref INPUT_TEST: SensorField = get_input::<Day15Pt1>("test.txt").unwrap();
+        static ref INPUT_MAIN: SensorField = get_input::<Day15Pt1>("input.txt").unwrap();
+    }
+
+    #[test]
+    fn test_readme_map() -> Result<()> {
+        // what the command in the readme prints
+        let input_str = read_input(15, "test.txt")?;
+        let map = draw_map(&input_str, Some(((-2, -2), (25, 16))), Some((8, 7)))?;
+        let readme = include_str!("readme.md");
+        assert!(readme.contains(&format!("```\n{}\n```", map)));
+        assert!(
+            readme.contains("map 15 --input src/day15/test.txt --view -2,-2,25,16 --sensor 8,7")
+        );
+        assert!(draw_map(&input_str, None, Some((1, 1))).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let map = Viewport::new((-10, 9), (30, 11))?.render(&INPUT_TEST)?;
+        let expected = "
+   -
+   1    -              1    1    2    2    3
+   0    5    0    5    0    5    0    5    0
+ 9 .........#########################.......
+10 ........####B######################......
+11 .......###S#############.###########.....";
+        assert_eq!(&expected[1..], map);
+
+        let row_10 = map.lines().nth(4).unwrap();
+        assert_eq!(
+            INPUT_TEST.coverage_on_row(10),
+            row_10.chars().filter(|&c| c == '#').count() as Coord
+        );
+
+        let viewport = Viewport::around(INPUT_TEST.sensors())?;
+        assert_eq!(Viewport::new((-2, 0), (25, 22))?, viewport);
+        assert!(Viewport::around(INPUT_MAIN.sensors())?
+            .render(&INPUT_MAIN)
+            .is_err());
+        assert!(Viewport::around(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pgm() -> Result<()> {
+        // one cell per pixel, the same as the map
+        let viewport = Viewport::new((0, 0), (20, 20))?;
+        let pgm = viewport.render_pgm(&INPUT_TEST, 21, 21)?;
+        let header = "P5\n21 21\n255\n";
+        assert_eq!(header.as_bytes(), &pgm[..header.len()]);
+        // the sensors and beacons are white too, the beacons can be there
+        let markers = markers(INPUT_TEST.sensors());
+        let free = pgm[header.len()..]
+            .iter()
+            .enumerate()
+            .filter(|(_, &level)| level == 255)
+            .map(|(idx, _)| ((idx % 21) as Coord, (idx / 21) as Coord))
+            .filter(|pt| !markers.contains_key(pt))
+            .collect::<Vec<_>>();
+        assert_eq!(INPUT_TEST.free_cells((0, 0), (20, 20)), free);
+
+        let input_str = read_input(15, "test.txt")?;
+        assert_eq!(
+            pgm,
+            draw_image(&input_str, Some(((0, 0), (20, 20))), (21, 21), false)?
+        );
+        assert_eq!(
+            viewport.render_ppm(&INPUT_TEST, 21, 21)?,
+            draw_image(&input_str, Some(((0, 0), (20, 20))), (21, 21), true)?
+        );
+
+        assert!(viewport.render_pgm(&INPUT_TEST, 22, 21).is_err());
+        assert!(viewport.render_pgm(&INPUT_TEST, 0, 21).is_err());
+        assert!(Viewport::new((1, 0), (0, 0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ppm_full_field() -> Result<()> {
+        let viewport = Viewport::new((0, 0), (4000000, 4000000))?;
+        let ppm = viewport.render_ppm(&INPUT_MAIN, 200, 100)?;
+        let header = "P6\n200 100\n255\n";
+        assert_eq!(header.as_bytes(), &ppm[..header.len()]);
+        assert_eq!(header.len() + 200 * 100 * 3, ppm.len());
+
+        let pixels = ppm[header.len()..].chunks(3).collect::<Vec<_>>();
+        // there is only one free cell, so every pixel is at least partly covered
+        assert!(pixels.iter().all(|&rgb| rgb != [255, 255, 255]));
+        let sensor = INPUT_MAIN.sensors()[0].pos;
+        if let Some(pixel) = viewport.pixel_of(sensor, 200, 100) {
+            assert_eq!(&[255, 0, 0], pixels[pixel]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use super::{
+    planner::{Plan, PlanParams},
+    Graph, Input,
+};
+use crate::search::{bfs, Visit};
+
+// graphviz export of the tunnels, `dot -Tsvg` draws it
+
+const AGENT_COLORS: [&str; 4] = ["red", "blue", "darkgreen", "orange"];
+
+// (from, to) => agent, for both directions of every edge the agent walks
+type Highlights = HashMap<(String, String), usize>;
+
+fn agent_color(agent: usize) -> &'static str {
+    AGENT_COLORS[agent % AGENT_COLORS.len()]
+}
+
+// the rooms the agents go through, the consecutive pairs are the edges they walk
+fn route_stops(plan: &Plan, params: &PlanParams) -> Vec<Vec<String>> {
+    plan.schedules
+        .iter()
+        .map(|schedule| {
+            [params.start_room.clone()]
+                .into_iter()
+                .chain(schedule.iter().map(|step| step.valve.clone()))
+                .collect()
+        })
+        .collect()
+}
+
+fn add_highlight(highlights: &mut Highlights, from: &str, to: &str, agent: usize) {
+    highlights.insert((from.to_string(), to.to_string()), agent);
+    highlights.insert((to.to_string(), from.to_string()), agent);
+}
+
+fn node_lines<'a>(
+    rooms: impl Iterator<Item = &'a str>,
+    input: &Input,
+    opened_by: &HashMap<&str, usize>,
+) -> Vec<String> {
+    rooms
+        .sorted()
+        .map(|name| {
+            let flow = input.get(name).map_or(0, |room| room.flow);
+            let style = match opened_by.get(name) {
+                Some(&agent) => format!(", style=filled, fillcolor={}", agent_color(agent)),
+                None if flow > 0 => ", shape=doublecircle".to_string(),
+                None => String::new(),
+            };
+            format!("  {} [label=\"{}\\nflow {}\"{}];", name, name, flow, style)
+        })
+        .collect()
+}
+
+fn edge_line(from: &str, to: &str, time: usize, highlights: &Highlights) -> String {
+    let style = match highlights.get(&(from.to_string(), to.to_string())) {
+        Some(&agent) => format!(", color={}, penwidth=3", agent_color(agent)),
+        None => String::new(),
+    };
+    format!("  {} -- {} [label=\"{}\"{}];", from, to, time, style)
+}
+
+fn opened_by(plan: Option<&Plan>) -> HashMap<&str, usize> {
+    plan.into_iter()
+        .flat_map(|plan| plan.schedules.iter().enumerate())
+        .flat_map(|(agent, schedule)| {
+            schedule
+                .iter()
+                .map(move |step| (step.valve.as_str(), agent))
+        })
+        .collect()
+}
+
+// every room and tunnel of the input, a tunnel takes one minute
+pub fn tunnels_to_dot(input: &Input, route: Option<(&Plan, &PlanParams)>) -> String {
+    let mut highlights = Highlights::new();
+    if let Some((plan, params)) = route {
+        for (agent, stops) in route_stops(plan, params).iter().enumerate() {
+            for (from, to) in stops.iter().tuple_windows() {
+                // the shortest way between the valves, as it was when compressing the graph
+                let path = bfs(input, [from.clone()], |name, _| {
+                    if name == to {
+                        Visit::Stop
+                    } else {
+                        Visit::Continue
+                    }
+                })
+                .goal_path()
+                .unwrap_or_default();
+                for (a, b) in path.iter().tuple_windows() {
+                    add_highlight(&mut highlights, a, b, agent);
+                }
+            }
+        }
+    }
+
+    let mut lines = vec!["graph tunnels {".to_string()];
+    lines.extend(node_lines(
+        input.keys().map(|it| it.as_str()),
+        input,
+        &opened_by(route.map(|(plan, _)| plan)),
+    ));
+    let tunnels = input
+        .iter()
+        .flat_map(|(from, room)| {
+            room.tunnels
+                .iter()
+                .map(move |to| (from.min(to), from.max(to)))
+        })
+        .unique()
+        .sorted();
+    lines.extend(tunnels.map(|(from, to)| edge_line(from, to, 1, &highlights)));
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+// the compressed graph of the valves, with the travel times between them
+pub fn graph_to_dot(input: &Input, graph: &Graph, route: Option<(&Plan, &PlanParams)>) -> String {
+    let mut highlights = Highlights::new();
+    if let Some((plan, params)) = route {
+        for (agent, stops) in route_stops(plan, params).iter().enumerate() {
+            for (from, to) in stops.iter().tuple_windows() {
+                add_highlight(&mut highlights, from, to, agent);
+            }
+        }
+    }
+
+    let mut lines = vec!["graph valves {".to_string()];
+    let rooms = graph.get_rooms();
+    lines.extend(node_lines(
+        rooms.iter().cloned(),
+        input,
+        &opened_by(route.map(|(plan, _)| plan)),
+    ));
+    for (from, to) in rooms.iter().sorted().tuple_combinations() {
+        if let Some(time) = graph.get_time(from, to) {
+            lines.push(edge_line(from, to, time, &highlights));
+        }
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
@@ -1,12 +1,22 @@
+mod dot;
 mod parser;
-
-use crate::solution::{Solution, SolutionInput};
+mod planner;
+mod valves;
+
+use crate::{
+    registry::Registry,
+    search::{dijkstra, SearchResult, SearchSpace, Visit},
+    solution::{Solution, SolutionInput},
+};
 use anyhow::{anyhow, bail, Result};
 
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::HashMap;
 
-use self::parser::parse_input;
+use self::{
+    parser::parse_input,
+    planner::{plan, PlanParams},
+};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Room {
@@ -16,29 +26,20 @@ pub struct Room {
 
 type Input = HashMap<String, Room>; // name => Room
 
-fn find_travel_time(input: &Input, from: &str, to: &str) -> Option<usize> {
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
-
-    queue.push_back((from, 0));
-    visited.insert(from);
-
-    while let Some((name, time)) = queue.pop_front() {
-        if let Some(room) = input.get(name) {
-            let next_time = time + 1;
-            for next in room.tunnels.iter() {
-                if next == to {
-                    return Some(next_time);
-                }
-
-                if !visited.contains(next.as_str()) {
-                    queue.push_back((next, next_time));
-                    visited.insert(next);
-                }
-            }
-        }
+impl SearchSpace for Input {
+    type Node = String;
+    type Cost = usize;
+
+    fn successors(&self, name: &String) -> impl Iterator<Item = (String, usize)> {
+        self.get(name)
+            .into_iter()
+            .flat_map(|room| room.tunnels.iter().map(|next| (next.clone(), 1)))
     }
-    None
+}
+
+// the travel time from the room to every room that can be reached from it
+fn travel_times_from(input: &Input, from: &str) -> SearchResult<String, usize> {
+    dijkstra(input, [from.to_string()], |_, _| Visit::Continue)
 }
 
 #[derive(Debug)]
@@ -79,9 +80,12 @@ fn make_graph_from_input(start_room: &str, input: &Input) -> Graph {
 
     let mut graph = Graph::new();
 
-    for (room_a, room_b) in non_zero_rooms.iter().tuple_combinations() {
-        if let Some(time) = find_travel_time(input, room_a, room_b) {
-            graph.add_connection(room_a, room_b, time)
+    for (idx, room_a) in non_zero_rooms.iter().enumerate() {
+        let travel_times = travel_times_from(input, room_a);
+        for room_b in non_zero_rooms[idx + 1..].iter() {
+            if let Some(time) = travel_times.cost_to(room_b) {
+                graph.add_connection(room_a, room_b, time)
+            }
         }
     }
 
@@ -98,6 +102,7 @@ fn calc_total_flow_for_visit_order(
     start_room: &str,
     order: &[&str],
     max_time: usize,
+    open_time: usize,
 ) -> Result<(usize, usize)> {
     let mut time_left = max_time;
     let mut total_flow = 0;
@@ -113,11 +118,11 @@ fn calc_total_flow_for_visit_order(
             .get(room_name)
             .ok_or_else(|| anyhow!("no room {:?} in input", room_name))?;
 
-        if (travel_time + 1) > time_left {
+        if (travel_time + open_time) > time_left {
             bail!("out of time");
         }
 
-        time_left -= travel_time + 1;
+        time_left -= travel_time + open_time;
         total_flow += time_left * room.flow;
 
         prev_room_name = room_name;
@@ -132,73 +137,36 @@ impl SolutionInput for Input {
     }
 }
 
-fn for_all_visit_orders<F: FnMut(&[&str], usize) -> Result<()>>(
-    start_room: &str,
-    rooms: &[&str],
-    input: &Input,
-    graph: &Graph,
-    max_time: usize,
-    mut cb: F,
-) -> Result<()> {
-    // recursively visit all possible orders of visit
-    fn gen<'a, F: FnMut(&[&str], usize) -> Result<()>>(
-        prev_room: &'a str,
-        vec: &mut Vec<&'a str>,
-        rooms: &mut HashSet<&'a str>,
-        input: &Input,
-        graph: &Graph,
-        time_left: usize,
-        total_flow: usize,
-        cb: &mut F,
-    ) -> Result<()> {
-        let mut traveled = false;
-        for next_room in rooms.iter().cloned().collect_vec() {
-            if let Some(travel_time) = graph.get_time(prev_room, next_room) {
-                if (travel_time + 1) <= time_left {
-                    rooms.remove(next_room);
-                    vec.push(next_room);
-                    let time_left = time_left - (travel_time + 1);
-
-                    let next_room_flow = input
-                        .get(next_room)
-                        .ok_or_else(|| anyhow!("no room {:?}", next_room))?
-                        .flow;
-                    let total_flow = total_flow + next_room_flow * time_left;
-                    gen(
-                        next_room, vec, rooms, input, graph, time_left, total_flow, cb,
-                    )?;
-
-                    rooms.insert(next_room);
-                    vec.pop();
-
-                    traveled = true;
-                }
-            }
-        }
-
-        if !traveled {
-            cb(vec, total_flow)?;
-        }
-
-        Ok(())
-    }
+// the setup of the part, the number of minutes and of the agents
+fn part_params(part: usize) -> Result<PlanParams> {
+    Ok(match part {
+        1 => PlanParams::default(),
+        2 => PlanParams {
+            max_time: 26,
+            agents: 2,
+            ..PlanParams::default()
+        },
+        _ => bail!("day 16 has parts 1 and 2, not {}", part),
+    })
+}
 
-    let mut rooms = HashSet::from_iter(rooms.iter().cloned());
-    rooms.remove(start_room);
-
-    // start the search
-    gen(
-        start_room,
-        &mut Vec::new(),
-        &mut rooms,
-        input,
-        graph,
-        max_time,
-        0,
-        &mut cb,
-    )?;
-
-    Ok(())
+// graphviz picture of the input for `aoc2022 dot 16`: every tunnel, or the graph of the
+// valves when compressed, with the route of the part on it if asked for
+pub fn to_dot(input_str: &str, part: usize, compressed: bool, route: bool) -> Result<String> {
+    let input = parse_input(input_str)?;
+    let params = part_params(part)?;
+    let plan = if route {
+        Some(plan(&input, &params)?)
+    } else {
+        None
+    };
+    let route = plan.as_ref().map(|plan| (plan, &params));
+    Ok(if compressed {
+        let graph = make_graph_from_input(&params.start_room, &input);
+        dot::graph_to_dot(&input, &graph, route)
+    } else {
+        dot::tunnels_to_dot(&input, route)
+    })
 }
 
 pub struct Day16Pt1;
@@ -210,39 +178,37 @@ impl Solution for Day16Pt1 {
     type TOutput = usize;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        let start_room = "AA";
-        let max_time = 30;
-        let graph = make_graph_from_input(start_room, input);
-        let rooms = graph.get_rooms();
+        Ok(plan(input, &part_params(1)?)?.total_flow)
+    }
+}
 
-        let mut max_flow = 0;
+pub struct Day16Pt2;
+impl Solution for Day16Pt2 {
+    const DAY: usize = 16;
+    const PART: usize = 2;
 
-        for_all_visit_orders(
-            start_room,
-            &rooms,
-            input,
-            &graph,
-            max_time,
-            |_order, flow| {
-                //let (flow, _time_left) =
-                // calc_total_flow_for_visit_order(input, &graph, start_room, order, max_time)
-                //     .with_context(|| anyhow!("visit order: {:?}", order))?;
-                max_flow = max_flow.max(flow);
-                Ok(())
-            },
-        )?;
+    type TInput = Input;
+    type TOutput = usize;
 
-        Ok(max_flow)
+    fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
+        Ok(plan(input, &part_params(2)?)?.total_flow)
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day16Pt1>();
+    registry.add::<Day16Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::util::get_input;
+    use crate::util::{get_input, read_input};
     use lazy_static::lazy_static;
     use ntest::timeout;
+    use planner::Step;
+    use valves::{Mask, Valves};
 
     lazy_static! {
         static ref INPUT_TEST: Input = get_input::<Day16Pt1>("test.txt").unwrap();
@@ -256,6 +222,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[timeout(1000)]
+    fn test_part2_result() -> Result<()> {
+        assert_eq!(2752, Day16Pt2::solve(&INPUT_MAIN)?);
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(1000)]
+    fn test_part2() -> Result<()> {
+        assert_eq!(1707, Day16Pt2::solve(&INPUT_TEST)?);
+        Ok(())
+    }
+
     #[test]
     fn test_part1() -> Result<()> {
         assert_eq!(1651, Day16Pt1::solve(&INPUT_TEST)?);
@@ -270,7 +250,7 @@ mod tests {
 
         assert_eq!(
             (364, 28),
-            calc_total_flow_for_visit_order(input, &graph, "AA", &["BB"], max_time)?
+            calc_total_flow_for_visit_order(input, &graph, "AA", &["BB"], max_time, 1)?
         );
 
         assert_eq!(
@@ -280,12 +260,254 @@ mod tests {
                 &graph,
                 "AA",
                 &["DD", "BB", "JJ", "HH", "EE", "CC"],
-                max_time
+                max_time,
+                1
             )?
         );
         Ok(())
     }
 
+    #[test]
+    fn test_best_flows() -> Result<()> {
+        let input: &Input = &INPUT_TEST;
+        let graph = make_graph_from_input("AA", input);
+        let valves = Valves::new(input, &graph, "AA")?;
+        assert_eq!(6, valves.len());
+
+        let mask_of = |names: &[&str]| -> Mask {
+            (0..valves.len())
+                .filter(|&it| names.contains(&valves.name(it)))
+                .map(|it| 1 << it)
+                .sum()
+        };
+        let explored = valves.explore(30, 1);
+        let best_flows = explored.best_flows();
+        assert_eq!(
+            1651,
+            best_flows[&mask_of(&["DD", "BB", "JJ", "HH", "EE", "CC"])]
+        );
+        assert_eq!(364, best_flows[&mask_of(&["BB"])]);
+        assert_eq!(0, best_flows[&0]);
+
+        let explored = valves.explore(26, 1);
+        let best_flows = explored.best_flows();
+        assert_eq!(
+            1707,
+            best_flows[&mask_of(&["JJ", "BB", "CC"])] + best_flows[&mask_of(&["DD", "HH", "EE"])]
+        );
+        Ok(())
+    }
+
+    fn steps(steps: &[(&str, usize, usize)]) -> Vec<Step> {
+        steps
+            .iter()
+            .map(|&(valve, minute, total_flow)| Step {
+                valve: valve.to_string(),
+                minute,
+                total_flow,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan() -> Result<()> {
+        let plan1 = plan(&INPUT_TEST, &PlanParams::default())?;
+        assert_eq!(1651, plan1.total_flow);
+        assert_eq!(
+            vec![steps(&[
+                ("DD", 2, 560),
+                ("BB", 5, 885),
+                ("JJ", 9, 1326),
+                ("HH", 17, 1612),
+                ("EE", 21, 1639),
+                ("CC", 24, 1651),
+            ])],
+            plan1.schedules
+        );
+
+        let params = PlanParams {
+            max_time: 26,
+            agents: 2,
+            ..PlanParams::default()
+        };
+        let plan2 = plan(&INPUT_TEST, &params)?;
+        assert_eq!(1707, plan2.total_flow);
+        let you = steps(&[("JJ", 3, 483), ("BB", 7, 730), ("CC", 9, 764)]);
+        let elephant = steps(&[("DD", 2, 480), ("HH", 7, 898), ("EE", 11, 943)]);
+        assert!(plan2.schedules.contains(&you) && plan2.schedules.contains(&elephant));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_params() -> Result<()> {
+        let input: &Input = &INPUT_TEST;
+        let graph = make_graph_from_input("AA", input);
+
+        let params = PlanParams {
+            max_time: 26,
+            agents: 3,
+            open_time: 2,
+            ..PlanParams::default()
+        };
+        let plan3 = plan(input, &params)?;
+        assert_eq!(3, plan3.schedules.len());
+        plan3.check(input, &graph, &params)?;
+
+        let mut broken = plan3.clone();
+        broken.schedules[0][0].total_flow += 1;
+        assert!(broken.check(input, &graph, &params).is_err());
+
+        let params = PlanParams {
+            start_room: "JJ".to_string(),
+            ..PlanParams::default()
+        };
+        let plan_jj = plan(input, &params)?;
+        // the start room valve can be opened right away
+        assert_eq!(
+            Some(("JJ", 1)),
+            plan_jj.schedules[0]
+                .first()
+                .map(|it| (it.valve.as_str(), it.minute))
+        );
+
+        assert!(plan(
+            input,
+            &PlanParams {
+                agents: 0,
+                ..PlanParams::default()
+            }
+        )
+        .is_err());
+        assert!(plan(
+            input,
+            &PlanParams {
+                start_room: "XX".to_string(),
+                ..PlanParams::default()
+            }
+        )
+        .is_err());
+        Ok(())
+    }
+
+    // AA, then a line of the valves, all with flow 1
+    fn line_of_valves(count: usize) -> Result<Input> {
+        let name = |idx: usize| {
+            format!(
+                "{}{}",
+                (b'A' + idx as u8 / 26) as char,
+                (b'A' + idx as u8 % 26) as char
+            )
+        };
+        let lines = (0..=count)
+            .map(|idx| {
+                let tunnels = [idx.checked_sub(1), Some(idx + 1).filter(|&it| it <= count)]
+                    .into_iter()
+                    .flatten()
+                    .map(name)
+                    .join(", ");
+                format!(
+                    "Valve {} has flow rate={}; tunnels lead to valves {}",
+                    name(idx),
+                    (idx > 0) as usize,
+                    tunnels
+                )
+            })
+            .join("\n");
+        parse_input(&lines)
+    }
+
+    #[test]
+    fn test_too_many_valves() -> Result<()> {
+        // every valve is worth the same, so keep the time short
+        let params = PlanParams {
+            agents: 3,
+            max_time: 12,
+            ..PlanParams::default()
+        };
+        assert_eq!(3, plan(&line_of_valves(64)?, &params)?.schedules.len());
+        assert!(plan(&line_of_valves(65)?, &PlanParams::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot() -> Result<()> {
+        let input = parse_input(
+            &[
+                "Valve AA has flow rate=0; tunnels lead to valves BB",
+                "Valve BB has flow rate=13; tunnels lead to valves AA, CC",
+                "Valve CC has flow rate=0; tunnels lead to valves BB, DD",
+                "Valve DD has flow rate=13; tunnels lead to valves CC",
+            ]
+            .join("\n"),
+        )?;
+        let graph = make_graph_from_input("AA", &input);
+        let params = PlanParams::default();
+        let plan = plan(&input, &params)?;
+
+        let expected = r#"graph tunnels {
+  AA [label="AA\nflow 0"];
+  BB [label="BB\nflow 13", shape=doublecircle];
+  CC [label="CC\nflow 0"];
+  DD [label="DD\nflow 13", shape=doublecircle];
+  AA -- BB [label="1"];
+  BB -- CC [label="1"];
+  CC -- DD [label="1"];
+}"#;
+        assert_eq!(expected, dot::tunnels_to_dot(&input, None));
+
+        let expected = r#"graph tunnels {
+  AA [label="AA\nflow 0"];
+  BB [label="BB\nflow 13", style=filled, fillcolor=red];
+  CC [label="CC\nflow 0"];
+  DD [label="DD\nflow 13", style=filled, fillcolor=red];
+  AA -- BB [label="1", color=red, penwidth=3];
+  BB -- CC [label="1", color=red, penwidth=3];
+  CC -- DD [label="1", color=red, penwidth=3];
+}"#;
+        assert_eq!(
+            expected,
+            dot::tunnels_to_dot(&input, Some((&plan, &params)))
+        );
+
+        let expected = r#"graph valves {
+  AA [label="AA\nflow 0"];
+  BB [label="BB\nflow 13", style=filled, fillcolor=red];
+  DD [label="DD\nflow 13", style=filled, fillcolor=red];
+  AA -- BB [label="1", color=red, penwidth=3];
+  AA -- DD [label="3"];
+  BB -- DD [label="2", color=red, penwidth=3];
+}"#;
+        assert_eq!(
+            expected,
+            dot::graph_to_dot(&input, &graph, Some((&plan, &params)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_dot() -> Result<()> {
+        let input_str = read_input(16, "test.txt")?;
+        let input = parse_input(&input_str)?;
+        assert_eq!(
+            dot::tunnels_to_dot(&input, None),
+            to_dot(&input_str, 1, false, false)?
+        );
+
+        let params = part_params(2)?;
+        let plan = plan(&input, &params)?;
+        let graph = make_graph_from_input("AA", &input);
+        let compressed = to_dot(&input_str, 2, true, true)?;
+        assert_eq!(
+            dot::graph_to_dot(&input, &graph, Some((&plan, &params))),
+            compressed
+        );
+        // the two agents in their colors
+        assert!(compressed.contains("fillcolor=red") && compressed.contains("fillcolor=blue"));
+        assert!(to_dot(&input_str, 3, false, false).is_err());
+        assert!(to_dot("Valve AA", 1, false, false).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_make_graph_from_input() -> Result<()> {
         let input = parse_input(
@@ -318,7 +540,10 @@ mod tests {
             ]
             .join("\n"),
         )?;
-        assert_eq!(Some(2), find_travel_time(&input, "AA", "DD"));
+        let travel_times = travel_times_from(&input, "AA");
+        assert_eq!(Some(2), travel_times.cost_to(&"DD".to_string()));
+        assert_eq!(Some(0), travel_times.cost_to(&"AA".to_string()));
+        assert_eq!(4, travel_times.reached().count());
         Ok(())
     }
 }
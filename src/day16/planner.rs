@@ -0,0 +1,203 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use itertools::Itertools;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use super::{
+    calc_total_flow_for_visit_order, make_graph_from_input,
+    valves::{Mask, Valves},
+    Graph, Input,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanParams {
+    pub start_room: String,
+    pub max_time: usize,
+    pub agents: usize,
+    pub open_time: usize, // minutes it takes to open a valve
+}
+
+impl Default for PlanParams {
+    // the part 1 setup
+    fn default() -> Self {
+        PlanParams {
+            start_room: "AA".to_string(),
+            max_time: 30,
+            agents: 1,
+            open_time: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub valve: String,
+    pub minute: usize,     // the valve is open at the end of this minute
+    pub total_flow: usize, // released till the end by the valves the agent opened so far
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub schedules: Vec<Vec<Step>>, // by agent
+    pub total_flow: usize,
+}
+
+impl Plan {
+    // replays every schedule with calc_total_flow_for_visit_order
+    pub fn check(&self, input: &Input, graph: &Graph, params: &PlanParams) -> Result<()> {
+        ensure!(
+            self.schedules.len() == params.agents,
+            "{} schedules for {} agents",
+            self.schedules.len(),
+            params.agents
+        );
+
+        let mut opened = HashSet::new();
+        let mut total_flow = 0;
+        for (agent, schedule) in self.schedules.iter().enumerate() {
+            for (idx, step) in schedule.iter().enumerate() {
+                ensure!(
+                    opened.insert(step.valve.as_str()),
+                    "valve {:?} is opened twice",
+                    step.valve
+                );
+
+                let order = schedule[..=idx]
+                    .iter()
+                    .map(|it| it.valve.as_str())
+                    .collect_vec();
+                let (flow, time_left) = calc_total_flow_for_visit_order(
+                    input,
+                    graph,
+                    &params.start_room,
+                    &order,
+                    params.max_time,
+                    params.open_time,
+                )
+                .with_context(|| anyhow!("agent {}, visit order {:?}", agent, order))?;
+                let minute = params.max_time - time_left;
+                ensure!(
+                    flow == step.total_flow && minute == step.minute,
+                    "agent {}, {:?} is replayed as total flow {} at minute {}",
+                    agent,
+                    step,
+                    flow,
+                    minute
+                );
+            }
+            total_flow += schedule.last().map_or(0, |it| it.total_flow);
+        }
+
+        ensure!(
+            total_flow == self.total_flow,
+            "total flow {} of the plan is replayed as {}",
+            self.total_flow,
+            total_flow
+        );
+        Ok(())
+    }
+}
+
+// the best combined flow found so far, with the valves opened by each of the agents
+struct Split {
+    flow: usize,
+    shares: Vec<Mask>,
+}
+
+// branch and bound over the opened sets of a single agent, the best ones first: each agent
+// takes a set from where the one before it took its own, disjoint from the sets taken so far
+fn add_shares(
+    sets: &[(usize, Mask)],
+    from: usize,
+    taken: Mask,
+    flow: usize,
+    shares: &mut Vec<Mask>,
+    best: &mut Split,
+) {
+    let agents_left = best.shares.len() - shares.len();
+    if agents_left == 0 {
+        if flow > best.flow {
+            best.flow = flow;
+            best.shares.clone_from(shares);
+        }
+        return;
+    }
+    for (idx, &(set_flow, opened)) in sets.iter().enumerate().skip(from) {
+        // none of the sets further on adds more
+        if flow + set_flow * agents_left <= best.flow {
+            break;
+        }
+        if opened & taken != 0 {
+            continue;
+        }
+        shares.push(opened);
+        add_shares(sets, idx, taken | opened, flow + set_flow, shares, best);
+        shares.pop();
+    }
+}
+
+// the valves opened by each of the agents, for the best combined flow
+fn split_valves(best_flows: &HashMap<Mask, usize>, agents: usize) -> Vec<Mask> {
+    let sets = best_flows
+        .iter()
+        .map(|(&opened, &flow)| (flow, opened))
+        .sorted_by_key(|&(flow, opened)| (Reverse(flow), opened))
+        .collect_vec();
+    // nobody opening anything is always possible
+    let mut best = Split {
+        flow: 0,
+        shares: vec![0; agents],
+    };
+    add_shares(&sets, 0, 0, 0, &mut Vec::with_capacity(agents), &mut best);
+    best.shares
+}
+
+pub fn plan(input: &Input, params: &PlanParams) -> Result<Plan> {
+    ensure!(params.agents > 0, "at least one agent is needed");
+    ensure!(params.open_time > 0, "opening a valve must take some time");
+    ensure!(
+        input.contains_key(&params.start_room),
+        "no start room {:?} in input",
+        params.start_room
+    );
+
+    let graph = make_graph_from_input(&params.start_room, input);
+    let valves = Valves::new(input, &graph, &params.start_room)?;
+    let explored = valves.explore(params.max_time, params.open_time);
+
+    let schedules = split_valves(explored.best_flows(), params.agents)
+        .into_iter()
+        .map(|opened| {
+            let mut total_flow = 0;
+            explored
+                .visit_order(opened)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(valve, time_left)| {
+                    total_flow += valves.flow(valve) * time_left;
+                    Step {
+                        valve: valves.name(valve).to_string(),
+                        minute: params.max_time - time_left,
+                        total_flow,
+                    }
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let total_flow = schedules
+        .iter()
+        .filter_map(|it| it.last())
+        .map(|it| it.total_flow)
+        .sum();
+
+    let plan = Plan {
+        schedules,
+        total_flow,
+    };
+    plan.check(input, &graph, params)
+        .context("the planned schedule does not replay")?;
+    Ok(plan)
+}
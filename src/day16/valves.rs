@@ -0,0 +1,174 @@
+use anyhow::{anyhow, ensure, Result};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use super::{Graph, Input};
+
+pub type Mask = u64; // bit i is set if the valve i is opened
+
+// one bit for every valve
+const MAX_VALVES: usize = Mask::BITS as usize;
+
+// compact form of the graph: valves are indices, travel times are in a matrix
+#[derive(Debug)]
+pub struct Valves {
+    names: Vec<String>, // the valves, then the start room (once more if it has a valve)
+    flows: Vec<usize>,  // by valve
+    times: Vec<Vec<Option<usize>>>, // travel times between all the rooms in names
+}
+
+impl Valves {
+    pub fn new(input: &Input, graph: &Graph, start_room: &str) -> Result<Valves> {
+        let mut names = graph
+            .get_rooms()
+            .into_iter()
+            .filter(|&it| it != start_room || input.get(it).is_some_and(|room| room.flow > 0))
+            .sorted()
+            .map(|it| it.to_string())
+            .collect_vec();
+        ensure!(
+            names.len() <= MAX_VALVES,
+            "too many valves: {}, at most {} are supported",
+            names.len(),
+            MAX_VALVES
+        );
+
+        let flows = names
+            .iter()
+            .map(|name| {
+                input
+                    .get(name)
+                    .map(|room| room.flow)
+                    .ok_or_else(|| anyhow!("no room {:?} in input", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        names.push(start_room.to_string());
+        let times = names
+            .iter()
+            .map(|from| names.iter().map(|to| graph.get_time(from, to)).collect())
+            .collect();
+
+        Ok(Valves {
+            names,
+            flows,
+            times,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    pub fn name(&self, valve: usize) -> &str {
+        &self.names[valve]
+    }
+
+    pub fn flow(&self, valve: usize) -> usize {
+        self.flows[valve]
+    }
+
+    fn start(&self) -> usize {
+        self.len()
+    }
+
+    // walks all the ways a single actor can open the valves,
+    // opening a valve takes open_time minutes besides the travel
+    pub fn explore(&self, max_time: usize, open_time: usize) -> Explored {
+        let mut best_flows = HashMap::new();
+
+        // every move takes time, so the states can be processed by the time left
+        let mut states: Vec<StatesAt> = vec![HashMap::new(); max_time + 1];
+        states[max_time].insert((self.start(), 0), (0, None));
+
+        for time_left in (0..=max_time).rev() {
+            let (later, current) = states.split_at_mut(time_left);
+            // in a fixed order, so ties are always broken the same way
+            let current = current[0].iter().sorted_by_key(|(&key, _)| key);
+            for (&(pos, opened), &(flow, _)) in current {
+                let state = (time_left, pos, opened);
+                let best = best_flows.entry(opened).or_insert(0);
+                *best = (*best).max(flow);
+
+                for next in (0..self.len()).filter(|&it| opened & (1 << it) == 0) {
+                    let Some(travel_time) = self.times[pos][next] else {
+                        continue;
+                    };
+                    if travel_time + open_time > time_left {
+                        continue;
+                    }
+                    let next_time_left = time_left - (travel_time + open_time);
+                    let next_flow = flow + self.flows[next] * next_time_left;
+                    let next_state = later[next_time_left]
+                        .entry((next, opened | (1 << next)))
+                        .or_insert((next_flow, Some(state)));
+                    if next_state.0 < next_flow {
+                        *next_state = (next_flow, Some(state));
+                    }
+                }
+            }
+        }
+
+        Explored {
+            best_flows,
+            max_time,
+            states,
+        }
+    }
+}
+
+type State = (usize, usize, Mask); // time left, position, opened
+
+// (position, opened) => the best flow and the previous state
+type StatesAt = HashMap<(usize, Mask), (usize, Option<State>)>;
+
+#[derive(Debug)]
+pub struct Explored {
+    best_flows: HashMap<Mask, usize>, // by opened
+    max_time: usize,
+    states: Vec<StatesAt>, // by time left
+}
+
+impl Explored {
+    // the best pressure released by opening exactly the valves of the mask, for every set
+    // that can be opened in time; usually a small part of all the sets
+    pub fn best_flows(&self) -> &HashMap<Mask, usize> {
+        &self.best_flows
+    }
+
+    // the first state with the best flow for the mask, in the order of exploring
+    fn best_state(&self, opened: Mask) -> Option<State> {
+        let mut best: Option<(usize, State)> = None;
+        for time_left in (0..=self.max_time).rev() {
+            let states = self.states[time_left]
+                .iter()
+                .filter(|(&(_, it), _)| it == opened)
+                .sorted_by_key(|(&key, _)| key);
+            for (&(pos, _), &(flow, _)) in states {
+                if best.is_none_or(|(best_flow, _)| best_flow < flow) {
+                    best = Some((flow, (time_left, pos, opened)));
+                }
+            }
+        }
+        best.map(|(_, state)| state)
+    }
+
+    // the best way to open exactly the valves of the mask:
+    // the valves in the order of opening with the time left after opening each of them
+    pub fn visit_order(&self, opened: Mask) -> Option<Vec<(usize, usize)>> {
+        let mut order = Vec::new();
+        let mut state = self.best_state(opened)?;
+        loop {
+            let (time_left, pos, opened) = state;
+            match self.states[time_left][&(pos, opened)].1 {
+                Some(prev) => {
+                    order.push((pos, time_left));
+                    state = prev;
+                }
+                None => break,
+            }
+        }
+        order.reverse();
+        Some(order)
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 
 use crate::{
+    registry::Registry,
     solution::{Solution, SolutionInput},
     util::split_parse,
 };
@@ -201,6 +202,11 @@ impl Solution for Day2Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day2Pt1>();
+    registry.add::<Day2Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
     use lazy_static::lazy_static;
@@ -3,7 +3,10 @@ mod pt1;
 mod sack_parts;
 
 use self::item::{Item, MAX_PRIORITY};
-use crate::solution::{Solution, SolutionInput};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 pub use pt1::Day3Pt1;
@@ -70,6 +73,11 @@ impl Solution for Day3Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day3Pt1>();
+    registry.add::<Day3Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -38,7 +38,7 @@ impl Debug for SackParts {
 impl FromStr for SackParts {
     type Err = Error;
     fn from_str(input_str: &str) -> Result<Self> {
-        if input_str.len() % 2 != 0 {
+        if !input_str.len().is_multiple_of(2) {
             bail!("odd length {}", input_str.len());
         }
         let len = input_str.len() / 2;
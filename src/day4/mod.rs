@@ -1,4 +1,8 @@
-use crate::solution::{Solution, SolutionInput};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+    util::IntervalSet,
+};
 use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
 
@@ -33,14 +37,11 @@ fn parse_range_pair(s: &str) -> Result<RangePair> {
 }
 
 fn range_contains_range(a: &Range, b: &Range) -> bool {
-    let rng = a.0..=a.1;
-    rng.contains(&b.0) && rng.contains(&b.1)
+    IntervalSet::from_interval(*a).contains_range(*b)
 }
 
 fn ranges_overlap(a: &Range, b: &Range) -> bool {
-    let a_left_of_b = a.1 < b.0;
-    let a_right_of_b = a.0 > b.1;
-    !a_left_of_b && !a_right_of_b
+    IntervalSet::from_interval(*a).overlaps(*b)
 }
 
 impl SolutionInput for Day4Input {
@@ -85,6 +86,11 @@ impl Solution for Day4Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day4Pt1>();
+    registry.add::<Day4Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
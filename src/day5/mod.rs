@@ -1,5 +1,8 @@
 use self::stack_set::StackSet;
-use crate::solution::{Solution, SolutionInput};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+};
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use itertools::Itertools;
 
@@ -85,6 +88,11 @@ impl Solution for Day5Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day5Pt1>();
+    registry.add::<Day5Pt2>();
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 
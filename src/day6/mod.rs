@@ -1,4 +1,7 @@
-use crate::solution::{Solution, SolutionInput};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+};
 use anyhow::{bail, Result};
 use std::collections::{HashMap, VecDeque};
 
@@ -74,6 +77,11 @@ fn find_first_window_with_all_distinct_chars(input: &str, window_size: usize) ->
     );
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day6Pt1>();
+    registry.add::<Day6Pt2>();
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 
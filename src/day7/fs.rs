@@ -65,7 +65,7 @@ impl Dir {
     }
 
     pub fn size(&self) -> usize {
-        self.entries.iter().map(|(_name, entry)| entry.size()).sum()
+        self.entries.values().map(|entry| entry.size()).sum()
     }
 
     pub fn entries(&self) -> impl Iterator<Item = (&String, &FSEntry)> + '_ {
@@ -1,6 +1,9 @@
 // there is a lot to improve here, but I have no time for this now
 use self::fs::{Dir, FSEntry, File};
-use crate::solution::{Solution, SolutionInput};
+use crate::{
+    registry::Registry,
+    solution::{Solution, SolutionInput},
+};
 use anyhow::{anyhow, bail, Context, Result};
 
 pub mod fs;
@@ -61,10 +64,10 @@ impl Solution for Day7Pt2 {
         run_commands(&mut root, &mut input.iter())?;
 
         let total_disk = 70000000;
-        let need = 30000000;
+        let need: usize = 30000000;
         let occupied = root.size();
         let free = total_disk - occupied;
-        let need_free = if free < need { need - free } else { 0 };
+        let need_free = need.saturating_sub(free);
 
         let mut dir_sizes = vec![];
         visit_all(&FSEntry::Dir(root), &mut |_| (), &mut |dir| {
@@ -166,6 +169,11 @@ fn run_commands<'a, T: Iterator<Item = &'a Command>>(
     Ok(())
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day7Pt1>();
+    registry.add::<Day7Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
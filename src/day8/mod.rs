@@ -1,4 +1,5 @@
 use crate::{
+    registry::Registry,
     solution::{Solution, SolutionInput},
     util::Vec2d,
 };
@@ -11,73 +12,50 @@ impl Day8Input {
         self.get(&(x, y)).cloned().unwrap_or(-1)
     }
 
-    pub fn count_visible_trees(&self) -> usize {
+    pub fn count_visible_trees(&self) -> Result<usize> {
+        // marks trees visible from the outside, looking from the start into the map
         fn look_from(
             map: &Day8Input,
             start: (isize, isize),
-            end: (isize, isize),
-            visible_trees: &mut [Vec<u8>],
+            dir: (isize, isize),
+            visible_trees: &mut Vec2d<bool, isize>,
         ) {
-            let dx = (end.0 - start.0).signum();
-            let dy = (end.1 - start.1).signum();
-            assert!(dx == 0 || dy == 0);
-
-            let mut x = start.0;
-            let mut y = start.1;
-            let mut prev_height = map.at(x - dx, y - dy);
-            while x != end.0 || y != end.1 {
-                let height = map.at(x, y);
-                let visible = height > prev_height;
-                if visible {
-                    visible_trees[y as usize][x as usize] += 1;
+            let mut prev_height = -1;
+            for (pt, &height) in map.ray(start, dir) {
+                if height > prev_height {
+                    visible_trees[pt] = true;
                     prev_height = height;
                 }
-                x += dx;
-                y += dy;
             }
         }
 
-        let mut visible_trees: Vec<Vec<u8>> =
-            vec![vec![0; self.size_x as usize]; self.size_y as usize];
+        let mut visible_trees = Vec2d::new(self.min, self.max, false)?;
 
-        for x in 0..self.size_x {
-            look_from(self, (x, 0), (x, self.size_y - 1), &mut visible_trees);
-            look_from(self, (x, self.size_y - 1), (x, 0), &mut visible_trees);
+        for x in self.min.0..=self.max.0 {
+            look_from(self, (x, self.min.1 - 1), (0, 1), &mut visible_trees);
+            look_from(self, (x, self.max.1 + 1), (0, -1), &mut visible_trees);
         }
 
-        for y in 0..self.size_y {
-            look_from(self, (0, y), (self.size_x - 1, y), &mut visible_trees);
-            look_from(self, (self.size_x - 1, y), (0, y), &mut visible_trees);
+        for y in self.min.1..=self.max.1 {
+            look_from(self, (self.min.0 - 1, y), (1, 0), &mut visible_trees);
+            look_from(self, (self.max.0 + 1, y), (-1, 0), &mut visible_trees);
         }
 
-        visible_trees
-            .into_iter()
-            .map(|line| line.into_iter().filter(|&v| v > 0).count())
-            .sum()
+        Ok(visible_trees.iter().filter(|(_, &v)| v).count())
     }
 
     pub fn viewing_distance(&self, start: (isize, isize), dir: (isize, isize)) -> usize {
-        let mut x = start.0;
-        let mut y = start.1;
-        let start_height = self.at(x, y);
+        let start_height = self.at(start.0, start.1);
         let mut distance = 0;
-        loop {
-            x += dir.0;
-            y += dir.1;
-
-            let height = self.at(x, y);
-            if height < 0 {
-                break;
-            }
-
+        for (_, &height) in self.ray(start, dir) {
             distance += 1;
-
             if height >= start_height {
                 break;
             }
         }
         distance
     }
+
     pub fn scenic_score_at(&self, x: isize, y: isize) -> usize {
         let up = self.viewing_distance((x, y), (0, -1));
         let down = self.viewing_distance((x, y), (0, 1));
@@ -87,15 +65,12 @@ impl Day8Input {
     }
 
     pub fn find_best_scenic_score(&self) -> Result<(usize, (isize, isize))> {
-        let mut best = None;
-        for y in 0..self.size_y {
-            for x in 0..self.size_x {
-                let score = self.scenic_score_at(x, y);
-                match best {
-                    None => best = Some((score, (x, y))),
-                    Some((prev_score, _)) if prev_score < score => best = Some((score, (x, y))),
-                    _ => (),
-                }
+        let mut best: Option<(usize, (isize, isize))> = None;
+        for (pt, _) in self.iter() {
+            let score = self.scenic_score_at(pt.0, pt.1);
+            match best {
+                Some((prev_score, _)) if prev_score >= score => (),
+                _ => best = Some((score, pt)),
             }
         }
         best.ok_or_else(|| anyhow!("not found"))
@@ -120,7 +95,7 @@ impl Solution for Day8Pt1 {
     type TOutput = usize;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput> {
-        Ok(input.count_visible_trees())
+        input.count_visible_trees()
     }
 }
 
@@ -138,6 +113,11 @@ impl Solution for Day8Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day8Pt1>();
+    registry.add::<Day8Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,10 +1,11 @@
 use crate::{
+    registry::Registry,
     solution::{Solution, SolutionInput},
-    util::split_parse,
+    util::{split_parse, ChunkedGrid},
 };
 use anyhow::{anyhow, bail, Context, Error, Result};
 use itertools::Itertools;
-use std::{collections::HashSet, str::FromStr};
+use std::str::FromStr;
 use Direction::*;
 
 type Point = (i32, i32);
@@ -116,16 +117,16 @@ impl SolutionInput for CommandList {
 }
 
 fn get_unique_tail_positions_count(rope_len: usize, commands: &CommandList) -> usize {
-    let mut visited: HashSet<Point> = HashSet::new();
+    let mut visited: ChunkedGrid<bool, i32> = ChunkedGrid::new(false);
     let mut rope = vec![(0, 0); rope_len];
-    visited.insert(*rope.last().unwrap());
+    visited[*rope.last().unwrap()] = true;
     for command in commands.iter() {
         for _ in 0..command.count {
             rope.move_by_dir(&command.dir);
-            visited.insert(*rope.last().unwrap());
+            visited[*rope.last().unwrap()] = true;
         }
     }
-    visited.len()
+    visited.iter().filter(|(_, &v)| v).count()
 }
 
 pub struct Day9Pt1;
@@ -156,6 +157,11 @@ impl Solution for Day9Pt2 {
     }
 }
 
+pub fn register(registry: &mut Registry) {
+    registry.add::<Day9Pt1>();
+    registry.add::<Day9Pt2>();
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,175 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    env,
+    fmt::{self, Display},
+    fs,
+    io::{self, ErrorKind, Read},
+    path::{Path, PathBuf},
+};
+
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+// Looks for input files of a day in "<dir>/day<N>/<name>" for every dir in order.
+#[derive(Debug, Clone)]
+pub struct InputLocator {
+    dirs: Vec<PathBuf>,
+}
+
+impl InputLocator {
+    pub fn new(dirs: Vec<PathBuf>) -> InputLocator {
+        InputLocator { dirs }
+    }
+
+    // $AOC_INPUT_DIR if set, then "src" of the current dir, then "src" of this crate checkout
+    pub fn from_env() -> InputLocator {
+        let mut dirs = Vec::new();
+        if let Some(dir) = env::var_os(INPUT_DIR_VAR) {
+            dirs.push(PathBuf::from(dir));
+        }
+        dirs.push(PathBuf::from("src"));
+        let crate_src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        if !dirs.contains(&crate_src) {
+            dirs.push(crate_src);
+        }
+        InputLocator::new(dirs)
+    }
+
+    pub fn candidates(&self, day: usize, name: &str) -> Vec<PathBuf> {
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(format!("day{}", day)).join(name))
+            .collect()
+    }
+
+    pub fn locate(&self, day: usize, name: &str) -> Result<PathBuf> {
+        let candidates = self.candidates(day, name);
+        for path in candidates.iter() {
+            match fs::metadata(path) {
+                Ok(_) => return Ok(path.clone()),
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err).context(format!("reading {:?}", path)),
+            }
+        }
+        bail!(
+            "input {:?} for day {} not found, tried:\n{}",
+            name,
+            day,
+            candidates
+                .iter()
+                .map(|it| format!("    {}", it.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+
+    pub fn read(&self, day: usize, name: &str) -> Result<String> {
+        read_input_file(&self.locate(day, name)?)
+    }
+}
+
+pub fn read_input_file(path: &Path) -> Result<String> {
+    let input_str = fs::read_to_string(path).with_context(|| anyhow!("reading {:?}", path))?;
+    Ok(trim_input(input_str))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InputSource {
+    #[default]
+    Default, // input.txt of every day, found by InputLocator
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    pub fn from_arg(arg: &str) -> InputSource {
+        match arg {
+            "-" => InputSource::Stdin,
+            _ => InputSource::File(PathBuf::from(arg)),
+        }
+    }
+
+    // reads the input shared by all selected solutions, None means every day reads its own
+    pub fn read_shared(&self) -> Result<Option<String>> {
+        let input_str = match self {
+            InputSource::Default => return Ok(None),
+            InputSource::File(path) => return read_input_file(path).map(Some),
+            InputSource::Stdin => {
+                let mut input_str = String::new();
+                io::stdin()
+                    .read_to_string(&mut input_str)
+                    .context("reading stdin")?;
+                input_str
+            }
+        };
+        Ok(Some(trim_input(input_str)))
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Default => write!(f, "input.txt"),
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "-"),
+        }
+    }
+}
+
+// parsers split by '\n' and do not expect an empty last line
+fn trim_input(mut input_str: String) -> String {
+    let len = input_str.trim_end_matches(['\n', '\r']).len();
+    input_str.truncate(len);
+    input_str
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let locator = InputLocator::new(vec![PathBuf::from("a"), PathBuf::from("b/c")]);
+        assert_eq!(
+            vec![
+                PathBuf::from("a/day15/test.txt"),
+                PathBuf::from("b/c/day15/test.txt")
+            ],
+            locator.candidates(15, "test.txt")
+        );
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let locator = InputLocator::new(vec![PathBuf::from("no_such_dir"), PathBuf::from("src")]);
+        assert!(locator.read(1, "test.txt")?.starts_with("1000\n2000"));
+
+        assert_eq!(
+            PathBuf::from("src/day1/test.txt"),
+            locator.locate(1, "test.txt")?
+        );
+
+        let err = locator.read(1, "no_such_file.txt").unwrap_err().to_string();
+        assert!(err.contains("no_such_dir/day1/no_such_file.txt"));
+        assert!(err.contains("src/day1/no_such_file.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_source() -> Result<()> {
+        assert_eq!(InputSource::Stdin, InputSource::from_arg("-"));
+        assert_eq!(None, InputSource::Default.read_shared()?);
+        assert_eq!(
+            Some("1000\n2000".to_string()),
+            InputSource::from_arg("src/day1/test.txt")
+                .read_shared()?
+                .map(|it| it[..9].to_string())
+        );
+        assert!(InputSource::from_arg("no_such_file.txt")
+            .read_shared()
+            .is_err());
+        assert_eq!("-", InputSource::Stdin.to_string());
+        assert_eq!("a\n\nb", trim_input("a\n\nb\r\n\n".to_string()));
+        Ok(())
+    }
+}
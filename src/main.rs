@@ -1,3 +1,6 @@
+mod answers;
+mod bench;
+mod cli;
 mod day1;
 mod day10;
 mod day11;
@@ -14,59 +17,272 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod input;
+mod registry;
+mod report;
+mod search;
 mod solution;
 mod util;
 
-use crate::solution::Solution;
-use anyhow::Result;
-use day1::{Day1Pt1, Day1Pt2};
-use day10::{Day10Pt1, Day10Pt2};
-use day11::{Day11Pt1, Day11Pt2};
-use day12::{Day12Pt1, Day12Pt2};
-use day13::{Day13Pt1, Day13Pt2};
-use day14::{Day14Pt1, Day14Pt2};
-use day15::{Day15Pt1, Day15Pt2};
-use day16::Day16Pt1;
-use day2::{Day2Pt1, Day2Pt2};
-use day3::{Day3Pt1, Day3Pt2};
-use day4::{Day4Pt1, Day4Pt2};
-use day5::{Day5Pt1, Day5Pt2};
-use day6::{Day6Pt1, Day6Pt2};
-use day7::{Day7Pt1, Day7Pt2};
-use day8::{Day8Pt1, Day8Pt2};
-use day9::{Day9Pt1, Day9Pt2};
+use answers::{AnswerStore, Verdict};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use cli::{
+    BenchArgs, Command, DotArgs, Filter, MapArgs, OutputFormat, RunArgs, TraceArgs, VerifyArgs,
+};
+use input::{read_input_file, InputLocator, InputSource};
+use registry::REGISTRY;
+use report::{to_json_line, BenchReport, RunReport};
+use solution::DynSolution;
+use std::fs;
 
-fn main() -> Result<()> {
-    Day1Pt1::run()?;
-    Day1Pt2::run()?;
-    Day2Pt1::run()?;
-    Day2Pt2::run()?;
-    Day3Pt1::run()?;
-    Day3Pt2::run()?;
-    Day4Pt1::run()?;
-    Day4Pt2::run()?;
-    Day5Pt1::run()?;
-    Day5Pt2::run()?;
-    Day6Pt1::run()?;
-    Day6Pt2::run()?;
-    Day7Pt1::run()?;
-    Day7Pt2::run()?;
-    Day8Pt1::run()?;
-    Day8Pt2::run()?;
-    Day9Pt1::run()?;
-    Day9Pt2::run()?;
-    Day10Pt1::run()?;
-    Day10Pt2::run()?;
-    Day11Pt1::run()?;
-    Day11Pt2::run()?;
-    Day12Pt1::run()?;
-    Day12Pt2::run()?;
-    Day13Pt1::run()?;
-    Day13Pt2::run()?;
-    Day14Pt1::run()?;
-    Day14Pt2::run()?;
-    Day15Pt1::run()?;
-    Day15Pt2::run()?;
-    Day16Pt1::run()?;
+fn select(filter: &Filter) -> Result<Vec<&'static dyn DynSolution>> {
+    let selected = REGISTRY
+        .iter()
+        .filter(|it| filter.matches(it.day(), it.part()))
+        .collect::<Vec<_>>();
+    ensure!(!selected.is_empty(), "no solutions match {:?}", filter);
+    Ok(selected)
+}
+
+struct LoadedInput {
+    name: String,
+    text: String,
+}
+
+fn load_input(
+    solution: &dyn DynSolution,
+    source: &InputSource,
+    shared_input: Option<&str>,
+) -> Result<LoadedInput> {
+    match shared_input {
+        Some(text) => Ok(LoadedInput {
+            name: source.to_string(),
+            text: text.to_string(),
+        }),
+        None => {
+            let path = InputLocator::from_env().locate(solution.day(), "input.txt")?;
+            Ok(LoadedInput {
+                text: read_input_file(&path)?,
+                name: path.display().to_string(),
+            })
+        }
+    }
+}
+
+fn report_failure(solution: &dyn DynSolution, err: &anyhow::Error) {
+    eprintln!(
+        "Day {} Part {} failed: {:?}",
+        solution.day(),
+        solution.part(),
+        err
+    );
+}
+
+fn run(args: &RunArgs) -> Result<()> {
+    let selected = select(&args.filter)?;
+    let shared_input = args.input.read_shared()?;
+    let mut failed = 0;
+    for solution in selected.iter() {
+        let (input_name, result) = match load_input(*solution, &args.input, shared_input.as_deref())
+        {
+            Ok(input) => (Some(input.name), solution.run_timed(&input.text)),
+            Err(err) => (None, Err(err)),
+        };
+        if result.is_err() {
+            failed += 1;
+        }
+
+        match (args.format, &result) {
+            (OutputFormat::Text, Ok(run)) => {
+                // multiline results (like Day 10 Part 2 picture) start on a new line
+                let sep = if run.answer.contains('\n') { "\n" } else { " " };
+                println!(
+                    "Day {} Part {} result:{}{}",
+                    solution.day(),
+                    solution.part(),
+                    sep,
+                    run.answer
+                );
+            }
+            (OutputFormat::Text, Err(err)) => report_failure(*solution, err),
+            (OutputFormat::Json, _) => {
+                let report = RunReport::new(solution.day(), solution.part(), input_name);
+                let report = match &result {
+                    Ok(run) => report.with_run(run),
+                    Err(err) => report.with_error(err),
+                };
+                println!("{}", to_json_line(&report));
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} solutions failed", failed, selected.len());
+    }
+    Ok(())
+}
+
+fn bench(args: &BenchArgs) -> Result<()> {
+    let run_args = &args.run_args;
+    let selected = select(&run_args.filter)?;
+    let shared_input = run_args.input.read_shared()?;
+    let mut results = Vec::new();
+    let mut failed = 0;
+    for solution in selected.iter() {
+        let (input_name, result) =
+            match load_input(*solution, &run_args.input, shared_input.as_deref()) {
+                Ok(input) => (
+                    Some(input.name),
+                    bench::bench(*solution, &input.text, args.runs),
+                ),
+                Err(err) => (None, Err(err)),
+            };
+
+        match (run_args.format, &result) {
+            (OutputFormat::Text, Ok(result)) => println!("{}", bench::format_result(result)),
+            (OutputFormat::Text, Err(err)) => report_failure(*solution, err),
+            (OutputFormat::Json, Ok(result)) => {
+                let report = BenchReport::new(result, input_name.unwrap_or_default());
+                println!("{}", to_json_line(&report));
+            }
+            (OutputFormat::Json, Err(err)) => {
+                let report = BenchReport::failed(solution.day(), solution.part(), input_name, err);
+                println!("{}", to_json_line(&report));
+            }
+        }
+
+        match result {
+            Ok(result) => results.push(result),
+            Err(_) => failed += 1,
+        }
+    }
+    if args.table && run_args.format == OutputFormat::Text && !results.is_empty() {
+        println!();
+        print!("{}", bench::format_table(&results));
+    }
+    if failed > 0 {
+        bail!("{} of {} solutions failed", failed, selected.len());
+    }
+    Ok(())
+}
+
+fn verify(args: &VerifyArgs) -> Result<()> {
+    let selected = select(&args.filter)?;
+    let mut store = AnswerStore::load(&AnswerStore::default_path())?;
+    let (mut passed, mut failed, mut missing, mut recorded) = (0, 0, 0, 0);
+    for solution in selected.iter() {
+        for (input, verdict) in answers::verify(*solution, &mut store, args.record, args.update) {
+            let status = match verdict {
+                Verdict::Pass => {
+                    passed += 1;
+                    "pass".to_string()
+                }
+                Verdict::Fail { expected, actual } => {
+                    failed += 1;
+                    format!("FAIL, expected {:?}, got {:?}", expected, actual)
+                }
+                Verdict::Error(err) => {
+                    failed += 1;
+                    format!("FAIL, {:?}", err)
+                }
+                Verdict::Missing => {
+                    missing += 1;
+                    "missing".to_string()
+                }
+                Verdict::Recorded(answer) => {
+                    recorded += 1;
+                    format!("recorded {:?}", answer)
+                }
+                Verdict::Updated { previous, answer } => {
+                    recorded += 1;
+                    format!("updated to {:?}, was {:?}", answer, previous)
+                }
+            };
+            println!(
+                "Day {} Part {} {}: {}",
+                solution.day(),
+                solution.part(),
+                input,
+                status
+            );
+        }
+    }
+    if recorded > 0 {
+        store.save()?;
+    }
+    println!(
+        "{} passed, {} failed, {} missing, {} recorded",
+        passed, failed, missing, recorded
+    );
+    if failed > 0 {
+        bail!("{} answers do not match", failed);
+    }
     Ok(())
 }
+
+fn list(filter: &Filter) -> Result<()> {
+    for solution in select(filter)? {
+        println!("Day {} Part {}", solution.day(), solution.part());
+    }
+    Ok(())
+}
+
+// the input of the only day a command works with
+fn read_day_input(day: usize, source: &InputSource) -> Result<String> {
+    match source.read_shared()? {
+        Some(text) => Ok(text),
+        None => InputLocator::from_env().read(day, "input.txt"),
+    }
+}
+
+fn dot(args: &DotArgs) -> Result<()> {
+    let input = read_day_input(16, &args.input)?;
+    println!(
+        "{}",
+        day16::to_dot(&input, args.part, args.compressed, args.route)?
+    );
+    Ok(())
+}
+
+fn map(args: &MapArgs) -> Result<()> {
+    let input = read_day_input(args.day, &args.input)?;
+    if args.day == 12 {
+        println!("{}", day12::draw_routes(&input, args.routes)?);
+        return Ok(());
+    }
+    match &args.image {
+        Some(path) => {
+            let image = day15::draw_image(&input, args.view, args.size, args.color())?;
+            fs::write(path, image).with_context(|| anyhow!("writing {:?}", path))?;
+        }
+        None => println!("{}", day15::draw_map(&input, args.view, args.sensor)?),
+    }
+    Ok(())
+}
+
+fn trace(args: &TraceArgs) -> Result<()> {
+    let input = read_day_input(10, &args.input)?;
+    let (trace, stop) = day10::trace(&input, args.cycles, &args.breakpoints, args.csv)?;
+    print!("{}", trace);
+    match stop {
+        day10::Stop::Breakpoint(pc) => eprintln!("stopped before instruction {}", pc),
+        day10::Stop::Halted => eprintln!("the program has ended"),
+        day10::Stop::Cycle | day10::Stop::Condition => {}
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match cli::parse_args(std::env::args().skip(1))? {
+        Command::Run(args) => run(&args),
+        Command::Bench(args) => bench(&args),
+        Command::Verify(args) => verify(&args),
+        Command::List(filter) => list(&filter),
+        Command::Dot(args) => dot(&args),
+        Command::Map(args) => map(&args),
+        Command::Trace(args) => trace(&args),
+        Command::Help => {
+            print!("{}", cli::usage());
+            Ok(())
+        }
+    }
+}
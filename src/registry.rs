@@ -0,0 +1,100 @@
+use lazy_static::lazy_static;
+
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day2, day3, day4, day5, day6, day7,
+    day8, day9,
+    solution::{DynSolution, Solution, SolutionWrapper},
+};
+
+#[derive(Default)]
+pub struct Registry {
+    solutions: Vec<Box<dyn DynSolution>>, // sorted by day, then by part
+}
+
+impl Registry {
+    pub fn add<T: Solution + 'static>(&mut self) {
+        let key = (T::DAY, T::PART);
+        match self
+            .solutions
+            .binary_search_by_key(&key, |it| (it.day(), it.part()))
+        {
+            Ok(_) => panic!("Day {} Part {} registered twice", key.0, key.1),
+            Err(pos) => self
+                .solutions
+                .insert(pos, Box::new(SolutionWrapper::<T>::new())),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DynSolution> {
+        self.solutions.iter().map(|it| it.as_ref())
+    }
+
+    #[cfg(test)]
+    pub fn find(&self, day: usize, part: usize) -> Option<&dyn DynSolution> {
+        self.iter().find(|it| it.day() == day && it.part() == part)
+    }
+}
+
+fn register_all_days() -> Registry {
+    let mut registry = Registry::default();
+    day1::register(&mut registry);
+    day2::register(&mut registry);
+    day3::register(&mut registry);
+    day4::register(&mut registry);
+    day5::register(&mut registry);
+    day6::register(&mut registry);
+    day7::register(&mut registry);
+    day8::register(&mut registry);
+    day9::register(&mut registry);
+    day10::register(&mut registry);
+    day11::register(&mut registry);
+    day12::register(&mut registry);
+    day13::register(&mut registry);
+    day14::register(&mut registry);
+    day15::register(&mut registry);
+    day16::register(&mut registry);
+    registry
+}
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = register_all_days();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::day1::{Day1Pt1, Day1Pt2};
+    use itertools::Itertools;
+
+    #[test]
+    fn test_registry_sorted() {
+        let keys = REGISTRY
+            .iter()
+            .map(|it| (it.day(), it.part()))
+            .collect_vec();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(Some((1, 1)), keys.first().cloned());
+    }
+
+    #[test]
+    fn test_find_and_run() -> anyhow::Result<()> {
+        let mut registry = Registry::default();
+        registry.add::<Day1Pt2>();
+        registry.add::<Day1Pt1>();
+
+        assert!(registry.find(1, 3).is_none());
+        let solution = registry.find(1, 1).unwrap();
+        assert_eq!("3", solution.run_on("1\n2\n\n3")?);
+        assert!(solution.run_on("foo").is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_twice() {
+        let mut registry = Registry::default();
+        registry.add::<Day1Pt1>();
+        registry.add::<Day1Pt1>();
+    }
+}
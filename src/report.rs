@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{
+    bench::{BenchResult, Stats},
+    solution::TimedRun,
+};
+
+// machine readable results, one json object per line
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+pub fn error_chain(err: &anyhow::Error) -> Vec<String> {
+    err.chain().map(|it| it.to_string()).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunReport {
+    pub day: usize,
+    pub part: usize,
+    pub input: Option<String>,
+    pub answer: Option<String>,
+    pub parse_ms: Option<f64>,
+    pub solve_ms: Option<f64>,
+    pub error: Option<Vec<String>>,
+}
+
+impl RunReport {
+    pub fn new(day: usize, part: usize, input: Option<String>) -> RunReport {
+        RunReport {
+            day,
+            part,
+            input,
+            answer: None,
+            parse_ms: None,
+            solve_ms: None,
+            error: None,
+        }
+    }
+
+    pub fn with_run(self, run: &TimedRun) -> RunReport {
+        RunReport {
+            answer: Some(run.answer.clone()),
+            parse_ms: Some(ms(run.parse_time)),
+            solve_ms: Some(ms(run.solve_time)),
+            ..self
+        }
+    }
+
+    pub fn with_error(self, err: &anyhow::Error) -> RunReport {
+        RunReport {
+            error: Some(error_chain(err)),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsReport {
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+}
+
+impl From<&Stats> for StatsReport {
+    fn from(stats: &Stats) -> StatsReport {
+        StatsReport {
+            min: ms(stats.min),
+            median: ms(stats.median),
+            max: ms(stats.max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchReport {
+    pub day: usize,
+    pub part: usize,
+    pub input: Option<String>,
+    pub runs: Option<usize>,
+    pub parse_ms: Option<StatsReport>,
+    pub solve_ms: Option<StatsReport>,
+    pub error: Option<Vec<String>>,
+}
+
+impl BenchReport {
+    pub fn new(result: &BenchResult, input: String) -> BenchReport {
+        BenchReport {
+            day: result.day,
+            part: result.part,
+            input: Some(input),
+            runs: Some(result.runs),
+            parse_ms: Some((&result.parse).into()),
+            solve_ms: Some((&result.solve).into()),
+            error: None,
+        }
+    }
+
+    pub fn failed(
+        day: usize,
+        part: usize,
+        input: Option<String>,
+        err: &anyhow::Error,
+    ) -> BenchReport {
+        BenchReport {
+            day,
+            part,
+            input,
+            runs: None,
+            parse_ms: None,
+            solve_ms: None,
+            error: Some(error_chain(err)),
+        }
+    }
+}
+
+pub fn to_json_line<T: Serialize>(report: &T) -> String {
+    // reports consist of strings and numbers only, they always serialize
+    serde_json::to_string(report).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use anyhow::{anyhow, Context, Result};
+    use serde_json::{json, Value};
+
+    fn parse(s: &str) -> Value {
+        serde_json::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_run_report() {
+        let run = TimedRun {
+            answer: "##..\n..##".to_string(),
+            parse_time: Duration::from_micros(1500),
+            solve_time: Duration::from_millis(2),
+        };
+        let report = RunReport::new(10, 2, Some("src/day10/input.txt".to_string())).with_run(&run);
+        let line = to_json_line(&report);
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            json!({
+                "day": 10,
+                "part": 2,
+                "input": "src/day10/input.txt",
+                "answer": "##..\n..##",
+                "parse_ms": 1.5,
+                "solve_ms": 2.0,
+                "error": null,
+            }),
+            parse(&line)
+        );
+    }
+
+    #[test]
+    fn test_error_chain() {
+        let err: Result<()> = Err(anyhow!("no way")).context("Day 16, Part 1");
+        let report = RunReport::new(16, 1, None).with_error(&err.unwrap_err());
+        assert_eq!(
+            json!(["Day 16, Part 1", "no way"]),
+            parse(&to_json_line(&report))["error"]
+        );
+    }
+
+    #[test]
+    fn test_bench_report() {
+        let stats = Stats {
+            min: Duration::from_millis(1),
+            median: Duration::from_millis(2),
+            max: Duration::from_millis(3),
+        };
+        let result = BenchResult {
+            day: 1,
+            part: 2,
+            runs: 3,
+            parse: stats,
+            solve: stats,
+        };
+        let value = parse(&to_json_line(&BenchReport::new(&result, "-".to_string())));
+        assert_eq!(json!(3), value["runs"]);
+        assert_eq!(
+            json!({"min": 1.0, "median": 2.0, "max": 3.0}),
+            value["solve_ms"]
+        );
+    }
+}
@@ -0,0 +1,454 @@
+use num_traits::Zero;
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+// anything bfs, dijkstra and a* can walk through
+pub trait SearchSpace {
+    type Node: Clone + Eq + Hash;
+    type Cost: Copy + Ord + Zero;
+
+    fn successors(&self, node: &Self::Node) -> impl Iterator<Item = (Self::Node, Self::Cost)>;
+
+    // same space, but only with the edges the predicate accepts
+    fn filter_edges<F>(&self, pred: F) -> FilterEdges<'_, Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self::Node, &Self::Node) -> bool,
+    {
+        FilterEdges { space: self, pred }
+    }
+}
+
+pub struct FilterEdges<'a, S, F> {
+    space: &'a S,
+    pred: F,
+}
+
+impl<S, F> SearchSpace for FilterEdges<'_, S, F>
+where
+    S: SearchSpace,
+    F: Fn(&S::Node, &S::Node) -> bool,
+{
+    type Node = S::Node;
+    type Cost = S::Cost;
+
+    fn successors(&self, node: &S::Node) -> impl Iterator<Item = (S::Node, S::Cost)> {
+        self.space
+            .successors(node)
+            .filter(move |(next, _)| (self.pred)(node, next))
+    }
+}
+
+// what to do with a node taken from the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue, // expand it
+    Stop,     // it is the goal, end the search
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<Node, Cost> {
+    // every reached node with its predecessor and the best known cost
+    reached: HashMap<Node, (Option<Node>, Cost)>,
+    pub goal: Option<Node>,
+}
+
+impl<Node: Clone + Eq + Hash, Cost: Copy> SearchResult<Node, Cost> {
+    pub fn reached(&self) -> impl Iterator<Item = (&Node, Cost)> {
+        self.reached.iter().map(|(node, &(_, cost))| (node, cost))
+    }
+
+    pub fn cost_to(&self, node: &Node) -> Option<Cost> {
+        self.reached.get(node).map(|&(_, cost)| cost)
+    }
+
+    // from one of the starts to the node, both included
+    pub fn path_to(&self, node: &Node) -> Option<Vec<Node>> {
+        let mut path = vec![node.clone()];
+        let mut prev = &self.reached.get(node)?.0;
+        while let Some(node) = prev {
+            path.push(node.clone());
+            prev = &self.reached[node].0;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<Node>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+// nodes are visited in the order of the number of steps, edge costs are summed up,
+// but they must be all the same for the costs to be the lowest ones
+pub fn bfs<S, V>(
+    space: &S,
+    starts: impl IntoIterator<Item = S::Node>,
+    mut visit: V,
+) -> SearchResult<S::Node, S::Cost>
+where
+    S: SearchSpace,
+    V: FnMut(&S::Node, S::Cost) -> Visit,
+{
+    let mut reached = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(entry) = reached.entry(start.clone()) {
+            entry.insert((None, S::Cost::zero()));
+            queue.push_back((start, S::Cost::zero()));
+        }
+    }
+
+    while let Some((node, cost)) = queue.pop_front() {
+        match visit(&node, cost) {
+            Visit::Continue => (),
+            Visit::Stop => {
+                return SearchResult {
+                    reached,
+                    goal: Some(node),
+                }
+            }
+        }
+
+        for (next, step_cost) in space.successors(&node) {
+            if let Entry::Vacant(entry) = reached.entry(next.clone()) {
+                let next_cost = cost + step_cost;
+                entry.insert((Some(node.clone()), next_cost));
+                queue.push_back((next, next_cost));
+            }
+        }
+    }
+
+    SearchResult {
+        reached,
+        goal: None,
+    }
+}
+
+// all the shortest ways from the starts, the number of steps is what counts, not the costs
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<Node> {
+    // every reached node with its number of steps and its predecessors on all the shortest ways
+    reached: HashMap<Node, (usize, Vec<Node>)>,
+}
+
+impl<Node: Clone + Eq + Hash> ShortestPaths<Node> {
+    pub fn reached(&self) -> impl Iterator<Item = (&Node, usize)> {
+        self.reached.iter().map(|(node, &(steps, _))| (node, steps))
+    }
+
+    // the shortest paths from one of the starts to the node, both included, at most `limit`
+    // of them; there can be more than fit in any number on open ground
+    pub fn paths_to(&self, node: &Node, limit: usize) -> Vec<Vec<Node>> {
+        let Some((_, prevs)) = self.reached.get(node) else {
+            return Vec::new();
+        };
+        if limit == 0 {
+            return Vec::new();
+        }
+        if prevs.is_empty() {
+            return vec![vec![node.clone()]];
+        }
+        let mut paths = Vec::new();
+        for prev in prevs.iter() {
+            if paths.len() == limit {
+                break;
+            }
+            paths.extend(self.paths_to(prev, limit - paths.len()));
+        }
+        for path in paths.iter_mut() {
+            path.push(node.clone());
+        }
+        paths
+    }
+}
+
+// bfs which keeps every predecessor at the same number of steps instead of the first one
+pub fn bfs_all_shortest<S: SearchSpace>(
+    space: &S,
+    starts: impl IntoIterator<Item = S::Node>,
+) -> ShortestPaths<S::Node> {
+    let mut reached: HashMap<S::Node, (usize, Vec<S::Node>)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(entry) = reached.entry(start.clone()) {
+            entry.insert((0, Vec::new()));
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, steps)) = queue.pop_front() {
+        for (next, _) in space.successors(&node) {
+            match reached.entry(next.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert((steps + 1, vec![node.clone()]));
+                    queue.push_back((next, steps + 1));
+                }
+                Entry::Occupied(mut entry) => {
+                    let (next_steps, prevs) = entry.get_mut();
+                    if *next_steps == steps + 1 && !prevs.contains(&node) {
+                        prevs.push(node.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    ShortestPaths { reached }
+}
+
+pub fn dijkstra<S, V>(
+    space: &S,
+    starts: impl IntoIterator<Item = S::Node>,
+    visit: V,
+) -> SearchResult<S::Node, S::Cost>
+where
+    S: SearchSpace,
+    V: FnMut(&S::Node, S::Cost) -> Visit,
+{
+    astar(space, starts, |_| S::Cost::zero(), visit)
+}
+
+struct Queued<Node, Cost> {
+    priority: Cost,
+    cost: Cost,
+    node: Node,
+}
+
+// BinaryHeap is a max heap, the lowest priority has to be the greatest
+impl<Node, Cost: Ord> Ord for Queued<Node, Cost> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<Node, Cost: Ord> PartialOrd for Queued<Node, Cost> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Node, Cost: Ord> PartialEq for Queued<Node, Cost> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<Node, Cost: Ord> Eq for Queued<Node, Cost> {}
+
+// the heuristic must never overestimate the remaining cost to the goal,
+// and must not drop by more than the edge cost along an edge
+pub fn astar<S, H, V>(
+    space: &S,
+    starts: impl IntoIterator<Item = S::Node>,
+    heuristic: H,
+    mut visit: V,
+) -> SearchResult<S::Node, S::Cost>
+where
+    S: SearchSpace,
+    H: Fn(&S::Node) -> S::Cost,
+    V: FnMut(&S::Node, S::Cost) -> Visit,
+{
+    let mut reached = HashMap::new();
+    let mut done = HashSet::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if let Entry::Vacant(entry) = reached.entry(start.clone()) {
+            entry.insert((None, S::Cost::zero()));
+            queue.push(Queued {
+                priority: heuristic(&start),
+                cost: S::Cost::zero(),
+                node: start,
+            });
+        }
+    }
+
+    while let Some(Queued { cost, node, .. }) = queue.pop() {
+        if !done.insert(node.clone()) {
+            continue; // there was a cheaper way to it
+        }
+
+        match visit(&node, cost) {
+            Visit::Continue => (),
+            Visit::Stop => {
+                return SearchResult {
+                    reached,
+                    goal: Some(node),
+                }
+            }
+        }
+
+        for (next, step_cost) in space.successors(&node) {
+            let next_cost = cost + step_cost;
+            let better = match reached.get(&next) {
+                Some(&(_, known_cost)) => next_cost < known_cost,
+                None => true,
+            };
+            if better {
+                reached.insert(next.clone(), (Some(node.clone()), next_cost));
+                queue.push(Queued {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    SearchResult {
+        reached,
+        goal: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::util::Vec2d;
+    use anyhow::Result;
+
+    // a -1-> b -1-> c -1-> d, and a -5-> d
+    struct Weighted;
+
+    impl SearchSpace for Weighted {
+        type Node = char;
+        type Cost = usize;
+
+        fn successors(&self, node: &char) -> impl Iterator<Item = (char, usize)> {
+            let edges: &[(char, usize)] = match node {
+                'a' => &[('d', 5), ('b', 1)],
+                'b' => &[('c', 1)],
+                'c' => &[('d', 1)],
+                _ => &[],
+            };
+            edges.iter().cloned()
+        }
+    }
+
+    fn stop_at(goal: char) -> impl FnMut(&char, usize) -> Visit {
+        move |&node, _| {
+            if node == goal {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let result = bfs(&Weighted, ['a'], stop_at('d'));
+        assert_eq!(Some('d'), result.goal);
+        assert_eq!(Some(vec!['a', 'd']), result.goal_path());
+        assert_eq!(Some(5), result.cost_to(&'d'));
+
+        let result = bfs(&Weighted, ['a'], |_, _| Visit::Continue);
+        assert_eq!(None, result.goal);
+        assert_eq!(4, result.reached().count());
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let result = dijkstra(&Weighted, ['a'], stop_at('d'));
+        assert_eq!(Some(vec!['a', 'b', 'c', 'd']), result.goal_path());
+        assert_eq!(Some(3), result.cost_to(&'d'));
+
+        let result = dijkstra(&Weighted, ['a'], stop_at('x'));
+        assert_eq!(None, result.goal);
+        assert_eq!(Some(3), result.cost_to(&'d'));
+        assert_eq!(None, result.path_to(&'x'));
+    }
+
+    #[test]
+    fn test_hooks() {
+        // stopping at the first node two away, c is not expanded, so d is still expensive
+        let result = dijkstra(&Weighted, ['a'], |_, cost| {
+            if cost >= 2 {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        });
+        assert_eq!(Some('c'), result.goal);
+        assert_eq!(Some(5), result.cost_to(&'d'));
+
+        let no_shortcut = Weighted.filter_edges(|&from, &to| (from, to) != ('a', 'd'));
+        let result = bfs(&no_shortcut, ['a'], stop_at('d'));
+        assert_eq!(Some(3), result.cost_to(&'d'));
+
+        let result = bfs(&Weighted, ['c', 'b'], stop_at('d'));
+        assert_eq!(Some(vec!['c', 'd']), result.goal_path());
+    }
+
+    #[test]
+    fn test_bfs_all_shortest() {
+        // the expensive edge is the fewest steps
+        let result = bfs_all_shortest(&Weighted, ['a']);
+        assert!(result.reached().any(|it| it == (&'d', 1)));
+        assert_eq!(vec![vec!['a', 'd']], result.paths_to(&'d', usize::MAX));
+        assert_eq!(vec![vec!['a', 'b', 'c']], result.paths_to(&'c', usize::MAX));
+
+        // three ways through a 3x2 grid
+        let map = Vec2d::<u8, i32>::parse("...\n...", (0, 0), |_x, _y, c| -> Result<u8> { Ok(c) })
+            .unwrap();
+        let result = bfs_all_shortest(&map, [(0, 0)]);
+        assert_eq!(6, result.reached().count());
+        let mut paths = result.paths_to(&(2, 1), usize::MAX);
+        paths.sort();
+        assert_eq!(
+            vec![
+                vec![(0, 0), (0, 1), (1, 1), (2, 1)],
+                vec![(0, 0), (1, 0), (1, 1), (2, 1)],
+                vec![(0, 0), (1, 0), (2, 0), (2, 1)],
+            ],
+            paths
+        );
+        let first_two = result.paths_to(&(2, 1), 2);
+        assert_eq!(2, first_two.len());
+        assert!(first_two.iter().all(|path| paths.contains(path)));
+        assert!(result.paths_to(&(2, 1), 0).is_empty());
+        assert_eq!(vec![vec![(0, 0)]], result.paths_to(&(0, 0), usize::MAX));
+        assert!(result.paths_to(&(5, 5), usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_astar_on_vec2d() -> Result<()> {
+        let map = Vec2d::<u8, i32>::parse(
+            &["....#", ".##.#", "...#.", "#...."].join("\n"),
+            (0, 0),
+            |_x, _y, c| -> Result<u8> { Ok(c) },
+        )?;
+        let open = map.filter_edges(|_, &to| map[to] == b'.');
+        let goal = (4, 3);
+        let manhattan = |&(x, y): &(i32, i32)| ((goal.0 - x).abs() + (goal.1 - y).abs()) as usize;
+
+        let result = astar(&open, [(0, 0)], manhattan, |&pt, _| {
+            if pt == goal {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        });
+        assert_eq!(Some(7), result.cost_to(&goal));
+        let path = result.goal_path().unwrap();
+        assert_eq!(8, path.len());
+        assert!(path
+            .windows(2)
+            .all(|w| manhattan(&w[0]).abs_diff(manhattan(&w[1])) == 1));
+
+        assert_eq!(
+            Some(7),
+            bfs(&open, [(0, 0)], |&pt, _| if pt == goal {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            })
+            .cost_to(&goal)
+        );
+        Ok(())
+    }
+}
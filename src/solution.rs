@@ -1,35 +1,99 @@
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
 
-use crate::util::get_input;
-
 pub trait SolutionInput: Debug + Sized {
     fn parse(input_str: &str) -> Result<Self>;
 }
 
+// canonical text of an answer, the one that is printed, stored and compared
+pub trait SolutionOutput {
+    fn to_answer(&self) -> String;
+}
+
+macro_rules! impl_solution_output_for_display {
+    ($($t:ty),*) => {
+        $(
+            impl SolutionOutput for $t {
+                fn to_answer(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_solution_output_for_display!(usize, i32, i64, String);
+
+// pictures, one line per item
+impl SolutionOutput for Vec<String> {
+    fn to_answer(&self) -> String {
+        self.join("\n")
+    }
+}
+
 pub trait Solution {
     const DAY: usize;
     const PART: usize;
 
     type TInput: SolutionInput;
-    type TOutput: Debug;
+    type TOutput: SolutionOutput;
 
     fn solve(input: &Self::TInput) -> Result<Self::TOutput>;
+}
+
+#[derive(Debug, Clone)]
+pub struct TimedRun {
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+// object safe counterpart of Solution, so solutions can be stored together and picked at runtime
+pub trait DynSolution: Send + Sync {
+    fn day(&self) -> usize;
+    fn part(&self) -> usize;
+    fn run_timed(&self, input_str: &str) -> Result<TimedRun>;
+
+    fn run_on(&self, input_str: &str) -> Result<String> {
+        Ok(self.run_timed(input_str)?.answer)
+    }
+}
+
+pub struct SolutionWrapper<T>(PhantomData<fn() -> T>);
+
+impl<T> SolutionWrapper<T> {
+    pub fn new() -> SolutionWrapper<T> {
+        SolutionWrapper(PhantomData)
+    }
+}
+
+impl<T: Solution> DynSolution for SolutionWrapper<T> {
+    fn day(&self) -> usize {
+        T::DAY
+    }
 
-    fn print_result(result: &Self::TOutput) {
-        print!("{:?}", result);
+    fn part(&self) -> usize {
+        T::PART
     }
 
-    fn run() -> Result<()> {
-        let input = get_input::<Self>("input.txt")?;
+    fn run_timed(&self, input_str: &str) -> Result<TimedRun> {
+        let start = Instant::now();
+        let input = T::TInput::parse(input_str).context("parsing input")?;
+        let parse_time = start.elapsed();
 
-        let output =
-            Self::solve(&input).context(format!("Day {}, Part {}", Self::DAY, Self::PART))?;
-        print!("Day {} Part {} result: ", Self::DAY, Self::PART);
-        Self::print_result(&output);
-        println!();
+        let start = Instant::now();
+        let output = T::solve(&input).context(format!("Day {}, Part {}", T::DAY, T::PART))?;
+        let solve_time = start.elapsed();
 
-        Ok(())
+        Ok(TimedRun {
+            answer: output.to_answer(),
+            parse_time,
+            solve_time,
+        })
     }
 }
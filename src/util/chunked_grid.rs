@@ -0,0 +1,199 @@
+use anyhow::Result;
+use num_traits::{PrimInt, Signed};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    ops::{Index, IndexMut},
+};
+
+use super::{grid::Bounds, Grid};
+
+const CHUNK_SIZE: usize = 16;
+
+// grid without fixed bounds: writing anywhere allocates the chunk around the point,
+// reading never written points gives the default item
+#[derive(Debug, Clone)]
+pub struct ChunkedGrid<ItemT, CoordT> {
+    chunks: HashMap<(CoordT, CoordT), Vec<ItemT>>,
+    default: ItemT,
+    bounds: Option<Bounds<CoordT>>,
+}
+
+impl<ItemT: Copy, CoordT: Debug + PrimInt + Signed + Hash> ChunkedGrid<ItemT, CoordT> {
+    pub fn new(default: ItemT) -> Self {
+        ChunkedGrid {
+            chunks: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    fn chunk_size() -> CoordT {
+        CoordT::from(CHUNK_SIZE).unwrap()
+    }
+
+    // chunk coordinate and position inside of the chunk, rounding towards negative infinity
+    fn split(c: CoordT) -> (CoordT, usize) {
+        let size = Self::chunk_size();
+        let (mut chunk, mut pos) = (c / size, c % size);
+        if pos.is_negative() {
+            chunk = chunk - CoordT::one();
+            pos = pos + size;
+        }
+        (chunk, pos.to_usize().unwrap())
+    }
+
+    fn locate(pt: &(CoordT, CoordT)) -> ((CoordT, CoordT), usize) {
+        let (chunk_x, x) = Self::split(pt.0);
+        let (chunk_y, y) = Self::split(pt.1);
+        ((chunk_x, chunk_y), x + y * CHUNK_SIZE)
+    }
+
+    pub fn is_inside(&self, pt: &(CoordT, CoordT)) -> bool {
+        match self.bounds {
+            Some((min, max)) => pt.0 >= min.0 && pt.0 <= max.0 && pt.1 >= min.1 && pt.1 <= max.1,
+            None => false,
+        }
+    }
+
+    // None outside of the bounds of the written points
+    pub fn get(&self, pt: &(CoordT, CoordT)) -> Option<&ItemT> {
+        if !self.is_inside(pt) {
+            return None;
+        }
+        let (key, offset) = Self::locate(pt);
+        match self.chunks.get(&key) {
+            Some(chunk) => Some(&chunk[offset]),
+            None => Some(&self.default),
+        }
+    }
+
+    // grows the grid to include the point
+    pub fn get_mut(&mut self, pt: &(CoordT, CoordT)) -> &mut ItemT {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                (min.0.min(pt.0), min.1.min(pt.1)),
+                (max.0.max(pt.0), max.1.max(pt.1)),
+            ),
+            None => (*pt, *pt),
+        });
+        let (key, offset) = Self::locate(pt);
+        let default = self.default;
+        &mut self
+            .chunks
+            .entry(key)
+            .or_insert_with(|| vec![default; CHUNK_SIZE * CHUNK_SIZE])[offset]
+    }
+
+    pub fn set(&mut self, pt: &(CoordT, CoordT), item: ItemT) {
+        *self.get_mut(pt) = item;
+    }
+
+    // every point of the allocated chunks, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = ((CoordT, CoordT), &ItemT)> + '_ {
+        let size = Self::chunk_size();
+        self.chunks.iter().flat_map(move |(key, chunk)| {
+            chunk.iter().enumerate().map(move |(offset, item)| {
+                let x = CoordT::from(offset % CHUNK_SIZE).unwrap();
+                let y = CoordT::from(offset / CHUNK_SIZE).unwrap();
+                ((key.0 * size + x, key.1 * size + y), item)
+            })
+        })
+    }
+}
+
+impl<ItemT: Copy, CoordT: Debug + PrimInt + Signed + Hash> Grid for ChunkedGrid<ItemT, CoordT> {
+    type Item = ItemT;
+    type Coord = CoordT;
+
+    fn get(&self, pt: &(CoordT, CoordT)) -> Option<&ItemT> {
+        self.get(pt)
+    }
+
+    fn set(&mut self, pt: &(CoordT, CoordT), item: ItemT) -> Result<()> {
+        self.set(pt, item);
+        Ok(())
+    }
+
+    fn bounds(&self) -> Option<Bounds<CoordT>> {
+        self.bounds
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((CoordT, CoordT), &ItemT)> + '_ {
+        self.iter()
+    }
+}
+
+impl<ItemT: Copy, CoordT: Debug + PrimInt + Signed + Hash> Index<(CoordT, CoordT)>
+    for ChunkedGrid<ItemT, CoordT>
+{
+    type Output = ItemT;
+
+    // unlike Vec2d this never panics, there is the default everywhere
+    fn index(&self, pt: (CoordT, CoordT)) -> &ItemT {
+        self.get(&pt).unwrap_or(&self.default)
+    }
+}
+
+impl<ItemT: Copy, CoordT: Debug + PrimInt + Signed + Hash> IndexMut<(CoordT, CoordT)>
+    for ChunkedGrid<ItemT, CoordT>
+{
+    fn index_mut(&mut self, pt: (CoordT, CoordT)) -> &mut ItemT {
+        self.get_mut(&pt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_split() {
+        type G = ChunkedGrid<u8, i32>;
+        assert_eq!((0, 0), G::split(0));
+        assert_eq!((0, 15), G::split(15));
+        assert_eq!((1, 0), G::split(16));
+        assert_eq!((-1, 15), G::split(-1));
+        assert_eq!((-1, 0), G::split(-16));
+        assert_eq!((-2, 15), G::split(-17));
+    }
+
+    #[test]
+    fn test_grow() {
+        let mut grid = ChunkedGrid::<u8, i32>::new(b'.');
+        assert_eq!(None, grid.get(&(0, 0)));
+        assert_eq!(b'.', grid[(0, 0)]);
+
+        grid[(-20, 3)] = b'a';
+        grid.set(&(100, -7), b'b');
+        assert_eq!(Some(((-20, -7), (100, 3))), Grid::bounds(&grid));
+        assert_eq!(Some(&b'a'), grid.get(&(-20, 3)));
+        assert_eq!(Some(&b'b'), grid.get(&(100, -7)));
+        assert_eq!(Some(&b'.'), grid.get(&(0, 0)));
+        assert_eq!(None, grid.get(&(0, 4)));
+
+        assert_eq!(
+            vec![((-20, 3), b'a'), ((100, -7), b'b')],
+            grid.iter()
+                .filter(|(_, &c)| c != b'.')
+                .map(|(pt, &c)| (pt, c))
+                .sorted()
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = ChunkedGrid::<u8, i32>::new(b'.');
+        assert_eq!("", grid.display_with(|_pt, &c| c as char).to_string());
+        grid[(-1, -1)] = b'#';
+        grid[(1, 0)] = b'o';
+        assert_eq!(
+            "#..\n..o",
+            grid.display_with(|_pt, &c| c as char).to_string()
+        );
+    }
+}
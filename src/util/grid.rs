@@ -0,0 +1,70 @@
+use anyhow::Result;
+use num_traits::{Num, One};
+use std::fmt::{self, Debug, Display};
+
+// (min, max) corners, both included
+pub type Bounds<CoordT> = ((CoordT, CoordT), (CoordT, CoordT));
+
+// api shared by the fixed size Vec2d and the growing ChunkedGrid
+pub trait Grid {
+    type Item: Copy;
+    type Coord: Debug + Copy + Num + PartialOrd;
+
+    fn get(&self, pt: &(Self::Coord, Self::Coord)) -> Option<&Self::Item>;
+
+    // fails if the grid cannot hold the point
+    #[allow(dead_code)]
+    fn set(&mut self, pt: &(Self::Coord, Self::Coord), item: Self::Item) -> Result<()>;
+
+    // (min, max) of the points in the grid, None if there are none
+    fn bounds(&self) -> Option<Bounds<Self::Coord>>;
+
+    #[allow(dead_code)]
+    fn iter(&self) -> impl Iterator<Item = ((Self::Coord, Self::Coord), &Self::Item)> + '_;
+
+    fn display_with<F>(&self, to_char: F) -> GridDisplay<'_, Self, F>
+    where
+        Self: Sized,
+        F: Fn((Self::Coord, Self::Coord), &Self::Item) -> char,
+    {
+        GridDisplay {
+            grid: self,
+            to_char,
+        }
+    }
+}
+
+pub struct GridDisplay<'a, G, F> {
+    grid: &'a G,
+    to_char: F,
+}
+
+impl<'a, G, F> Display for GridDisplay<'a, G, F>
+where
+    G: Grid,
+    F: Fn((G::Coord, G::Coord), &G::Item) -> char,
+{
+    // one line per row of the bounds, no trailing newline
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((min, max)) = self.grid.bounds() else {
+            return Ok(());
+        };
+        let one = G::Coord::one();
+        let mut y = min.1;
+        while y <= max.1 {
+            if y != min.1 {
+                writeln!(f)?;
+            }
+            let mut x = min.0;
+            while x <= max.0 {
+                match self.grid.get(&(x, y)) {
+                    Some(item) => write!(f, "{}", (self.to_char)((x, y), item))?,
+                    None => write!(f, " ")?,
+                }
+                x = x + one;
+            }
+            y = y + one;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+// a set of integers kept as sorted inclusive intervals,
+// the intervals never overlap or touch, so the form is always the same for the same set
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: PrimInt + Debug> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PrimInt + Debug> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+
+    // start and end included, empty when start > end
+    pub fn from_interval(interval: (T, T)) -> IntervalSet<T> {
+        let mut result = Self::new();
+        result.insert(interval);
+        result
+    }
+
+    pub fn intervals(&self) -> &[(T, T)] {
+        &self.intervals
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // the number of integers in the set, it may not fit in T: a full u8 set has 256;
+    // only the full set of u128 or i128 does not fit in u128, it gives u128::MAX
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().fold(0u128, |acc, &(start, end)| {
+            acc.saturating_add(Self::width(start, end))
+                .saturating_add(1)
+        })
+    }
+
+    // end - start as u128, start <= end
+    fn width(start: T, end: T) -> u128 {
+        match (start.to_i128(), end.to_i128()) {
+            (Some(start), Some(end)) => end.abs_diff(start),
+            // one of them is above i128::MAX, so T is unsigned and both fit in u128
+            _ => end.to_u128().unwrap() - start.to_u128().unwrap(),
+        }
+    }
+
+    // the index of the first interval which ends at or after x
+    fn first_ending_from(&self, x: T) -> usize {
+        self.intervals.partition_point(|&(_, end)| end < x)
+    }
+
+    // the index of the first interval which starts after x
+    fn first_starting_after(&self, x: T) -> usize {
+        self.intervals.partition_point(|&(start, _)| start <= x)
+    }
+
+    pub fn insert(&mut self, (start, end): (T, T)) {
+        if start > end {
+            return;
+        }
+        // the touching neighbours are merged too
+        let lo = self
+            .intervals
+            .partition_point(|&(_, e)| e < start && e + T::one() < start);
+        let hi = self
+            .intervals
+            .partition_point(|&(s, _)| s <= end || s - T::one() <= end);
+        let merged = if lo < hi {
+            (
+                start.min(self.intervals[lo].0),
+                end.max(self.intervals[hi - 1].1),
+            )
+        } else {
+            (start, end)
+        };
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    #[allow(dead_code)]
+    pub fn remove(&mut self, (start, end): (T, T)) {
+        if start > end {
+            return;
+        }
+        let lo = self.first_ending_from(start);
+        let hi = self.first_starting_after(end);
+        if lo >= hi {
+            return;
+        }
+        let (first_start, _) = self.intervals[lo];
+        let (_, last_end) = self.intervals[hi - 1];
+        let left = (first_start < start).then(|| (first_start, start - T::one()));
+        let right = (last_end > end).then(|| (end + T::one(), last_end));
+        self.intervals.splice(lo..hi, left.into_iter().chain(right));
+    }
+
+    #[allow(dead_code)]
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for &interval in other.intervals.iter() {
+            result.insert(interval);
+        }
+        result
+    }
+
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (self.intervals.iter(), other.intervals.iter());
+        let (mut next_a, mut next_b) = (a.next(), b.next());
+        while let (Some(&(a_start, a_end)), Some(&(b_start, b_end))) = (next_a, next_b) {
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                intervals.push((start, end));
+            }
+            // the one ending first can't intersect anything else
+            if a_end < b_end {
+                next_a = a.next();
+            } else {
+                next_b = b.next();
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    #[allow(dead_code)]
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for &interval in other.intervals.iter() {
+            result.remove(interval);
+        }
+        result
+    }
+
+    // the intervals of the range not in the set
+    #[allow(dead_code)]
+    pub fn gaps(&self, range: (T, T)) -> IntervalSet<T> {
+        Self::from_interval(range).difference(self)
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.intervals
+            .get(self.first_ending_from(x))
+            .is_some_and(|&(start, _)| start <= x)
+    }
+
+    // every integer of the range is in the set, true for an empty range
+    pub fn contains_range(&self, (start, end): (T, T)) -> bool {
+        start > end
+            || self
+                .intervals
+                .get(self.first_ending_from(end))
+                .is_some_and(|&(s, _)| s <= start)
+    }
+
+    // at least one integer of the range is in the set
+    pub fn overlaps(&self, (start, end): (T, T)) -> bool {
+        start <= end
+            && self
+                .intervals
+                .get(self.first_ending_from(start))
+                .is_some_and(|&(s, _)| s <= end)
+    }
+}
+
+impl<T: PrimInt + Debug> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for interval in iter {
+            result.insert(interval);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_insert() {
+        let mut set = IntervalSet::new();
+        set.insert((5, 7));
+        set.insert((1, 2));
+        set.insert((10, 9)); // empty
+        assert_eq!(&[(1, 2), (5, 7)], set.intervals());
+        set.insert((3, 4)); // touches both
+        assert_eq!(&[(1, 7)], set.intervals());
+        set.insert((-5, 20));
+        assert_eq!(&[(-5, 20)], set.intervals());
+        assert_eq!(26, set.len());
+
+        let mut set = IntervalSet::<u8>::from_interval((250, 255));
+        set.insert((0, 0));
+        set.insert((1, 249));
+        assert_eq!(&[(0, 255)], set.intervals());
+    }
+
+    #[test]
+    fn test_len() {
+        // more than fits in the type of the elements
+        assert_eq!(256, IntervalSet::<u8>::from_interval((0, 255)).len());
+        assert_eq!(256, IntervalSet::<i8>::from_interval((-128, 127)).len());
+        let set: IntervalSet<i64> = [(i64::MIN, -1), (1, i64::MAX)].into_iter().collect();
+        assert_eq!(u64::MAX as u128, set.len());
+        let set = IntervalSet::<u128>::from_interval((1, u128::MAX));
+        assert_eq!(u128::MAX, set.len());
+        let set = IntervalSet::<u128>::from_interval((0, u128::MAX));
+        assert_eq!(u128::MAX, set.len());
+        assert_eq!(0, IntervalSet::<u8>::new().len());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: IntervalSet<i32> = [(0, 10), (20, 30)].into_iter().collect();
+        set.remove((5, 5));
+        assert_eq!(&[(0, 4), (6, 10), (20, 30)], set.intervals());
+        set.remove((8, 25));
+        assert_eq!(&[(0, 4), (6, 7), (26, 30)], set.intervals());
+        set.remove((-100, 100));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: IntervalSet<i32> = [(0, 10), (20, 30)].into_iter().collect();
+        let b: IntervalSet<i32> = [(5, 25), (28, 40)].into_iter().collect();
+        assert_eq!(&[(0, 40)], a.union(&b).intervals());
+        assert_eq!(
+            &[(5, 10), (20, 25), (28, 30)],
+            a.intersection(&b).intervals()
+        );
+        assert_eq!(&[(0, 4), (26, 27)], a.difference(&b).intervals());
+        assert_eq!(&[(-2, -1), (11, 19)], a.gaps((-2, 22)).intervals());
+    }
+
+    #[test]
+    fn test_membership() {
+        let set: IntervalSet<usize> = [(2, 4), (7, 9)].into_iter().collect();
+        assert!(set.contains(2) && set.contains(9));
+        assert!(!set.contains(1) && !set.contains(5) && !set.contains(10));
+        assert!(set.contains_range((7, 9)));
+        assert!(!set.contains_range((4, 7)));
+        assert!(set.contains_range((5, 4)));
+        assert!(set.overlaps((4, 7)) && set.overlaps((0, 2)));
+        assert!(!set.overlaps((5, 6)) && !set.overlaps((10, 20)));
+    }
+
+    // the same operations on a set of small numbers, one bit per number
+    const BITS: i32 = 64;
+
+    fn to_bits(set: &IntervalSet<i32>) -> u64 {
+        set.intervals()
+            .iter()
+            .flat_map(|&(start, end)| start..=end)
+            .fold(0, |acc, x| acc | (1 << x))
+    }
+
+    fn range_bits((start, end): (i32, i32)) -> u64 {
+        (start..=end).fold(0, |acc, x| acc | (1 << x))
+    }
+
+    fn interval() -> impl Strategy<Value = (i32, i32)> {
+        (0..BITS, 0..BITS)
+    }
+
+    fn interval_set() -> impl Strategy<Value = IntervalSet<i32>> {
+        prop::collection::vec(interval(), 0..8).prop_map(|it| it.into_iter().collect())
+    }
+
+    fn is_normalized(set: &IntervalSet<i32>) -> bool {
+        set.intervals().iter().all(|&(start, end)| start <= end)
+            && set.intervals().windows(2).all(|w| w[0].1 + 1 < w[1].0)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_insert_remove(ops in prop::collection::vec((any::<bool>(), interval()), 0..16)) {
+            let mut set = IntervalSet::new();
+            let mut bits = 0u64;
+            for (insert, range) in ops {
+                if insert {
+                    set.insert(range);
+                    bits |= range_bits(range);
+                } else {
+                    set.remove(range);
+                    bits &= !range_bits(range);
+                }
+                prop_assert!(is_normalized(&set));
+                prop_assert_eq!(bits, to_bits(&set));
+                prop_assert_eq!(bits.count_ones() as u128, set.len());
+            }
+        }
+
+        #[test]
+        fn prop_set_operations(a in interval_set(), b in interval_set(), range in interval()) {
+            let (a_bits, b_bits) = (to_bits(&a), to_bits(&b));
+            for (bits, set) in [
+                (a_bits | b_bits, a.union(&b)),
+                (a_bits & b_bits, a.intersection(&b)),
+                (a_bits & !b_bits, a.difference(&b)),
+                (range_bits(range) & !a_bits, a.gaps(range)),
+            ] {
+                prop_assert!(is_normalized(&set));
+                prop_assert_eq!(bits, to_bits(&set));
+            }
+        }
+
+        #[test]
+        fn prop_membership(set in interval_set(), range in interval(), x in 0..BITS) {
+            let bits = to_bits(&set);
+            let range_bits = range_bits(range);
+            prop_assert_eq!(bits & (1 << x) != 0, set.contains(x));
+            prop_assert_eq!(bits & range_bits == range_bits, set.contains_range(range));
+            prop_assert_eq!(bits & range_bits != 0, set.overlaps(range));
+        }
+    }
+}
@@ -0,0 +1,36 @@
+mod chunked_grid;
+mod grid;
+mod interval_set;
+mod vec2d;
+
+use anyhow::{Context, Result};
+use std::str::FromStr;
+
+use crate::input::InputLocator;
+#[cfg(test)]
+use crate::solution::{Solution, SolutionInput};
+
+pub use chunked_grid::ChunkedGrid;
+pub use grid::Grid;
+pub use interval_set::IntervalSet;
+pub use vec2d::Vec2d;
+
+pub fn read_input(day: usize, name: &str) -> Result<String> {
+    InputLocator::from_env().read(day, name)
+}
+
+#[cfg(test)]
+pub fn get_input<T: Solution + ?Sized>(name: &str) -> Result<T::TInput> {
+    let input_str = read_input(T::DAY, name)?;
+    T::TInput::parse(&input_str).context(format!("parsing {:?} for day {}", name, T::DAY))
+}
+
+pub fn split_parse<T>(data: &str, sep: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    Result<T, <T as FromStr>::Err>: Context<T, <T as FromStr>::Err>,
+{
+    data.split(sep)
+        .map(|it| it.parse::<T>().context(format!("can't parse '{}'", it)))
+        .collect()
+}
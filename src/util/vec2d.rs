@@ -0,0 +1,397 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use num_traits::{FromPrimitive, Num, Signed, ToPrimitive};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    iter::successors,
+    ops::{Index, IndexMut},
+};
+
+use super::{grid::Bounds, Grid};
+use crate::search::SearchSpace;
+
+#[derive(Debug)]
+pub struct Vec2d<ItemT, CoordT> {
+    vec: Vec<ItemT>,
+    pub min: (CoordT, CoordT),
+    pub max: (CoordT, CoordT),
+    pub size_x: CoordT,
+    #[allow(dead_code)]
+    pub size_y: CoordT,
+}
+
+impl<ItemT: Copy, CoordT: Debug + Copy + Num + PartialOrd + ToPrimitive + FromPrimitive>
+    Vec2d<ItemT, CoordT>
+{
+    pub fn new(min: (CoordT, CoordT), max: (CoordT, CoordT), init_val: ItemT) -> Result<Self> {
+        let size_x = CoordT::one() + max.0 - min.0;
+        let size_y = CoordT::one() + max.1 - min.1;
+        let vec_size = (size_x * size_y)
+            .to_usize()
+            .ok_or_else(|| anyhow!("failed convert vec_size to usize"))?;
+        let vec = vec![init_val; vec_size];
+        Ok(Vec2d {
+            vec,
+            min,
+            max,
+            size_x,
+            size_y,
+        })
+    }
+
+    pub fn parse<ElemPredE, ElemPred>(
+        input_str: &str,
+        min: (CoordT, CoordT),
+        mut elem_pred: ElemPred,
+    ) -> Result<Vec2d<ItemT, CoordT>>
+    where
+        Result<ItemT, ElemPredE>: Context<ItemT, ElemPredE>,
+        ElemPred: FnMut(usize, usize, u8) -> Result<ItemT, ElemPredE>,
+    {
+        let mut size_x = None;
+        let mut size_y = 0;
+        let mut vec = Vec::new();
+        for (y, line) in input_str.split('\n').enumerate() {
+            let mut line_len = 0;
+
+            for (x, c) in line.bytes().enumerate() {
+                let elem = elem_pred(x, y, c)
+                    .with_context(|| anyhow!("at line {}, pos {}", y + 1, x + 1))?;
+                vec.push(elem);
+
+                line_len += 1;
+            }
+
+            match size_x {
+                None => size_x = Some(line_len),
+                Some(size_x) => ensure!(size_x == line_len),
+            }
+
+            size_y += 1;
+        }
+
+        let size_x = size_x.ok_or_else(|| anyhow!("No input"))?;
+
+        let max = (
+            min.0 + CoordT::from_usize(size_x).unwrap() - CoordT::one(),
+            min.1 + CoordT::from_usize(size_y).unwrap() - CoordT::one(),
+        );
+
+        Ok(Vec2d {
+            vec,
+            min,
+            max,
+            size_x: CoordT::from_usize(size_x).unwrap(),
+            size_y: CoordT::from_usize(size_y).unwrap(),
+        })
+    }
+
+    pub fn get(&self, pt: &(CoordT, CoordT)) -> Option<&ItemT> {
+        if self.is_inside(pt) {
+            Some(&self.vec[self.offset(pt)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pt: &(CoordT, CoordT)) -> Option<&mut ItemT> {
+        if self.is_inside(pt) {
+            let offset = self.offset(pt);
+            Some(&mut self.vec[offset])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_inside(&self, pt: &(CoordT, CoordT)) -> bool {
+        pt.0 >= self.min.0 && pt.0 <= self.max.0 && pt.1 >= self.min.1 && pt.1 <= self.max.1
+    }
+
+    fn offset(&self, pt: &(CoordT, CoordT)) -> usize {
+        // not that I like these unwraps...
+        ((pt.0 - self.min.0) + (pt.1 - self.min.1) * self.size_x)
+            .to_usize()
+            .unwrap()
+    }
+
+    fn width(&self) -> usize {
+        self.size_x.to_usize().unwrap()
+    }
+
+    fn point_at(&self, offset: usize) -> (CoordT, CoordT) {
+        let width = self.width();
+        (
+            self.min.0 + CoordT::from_usize(offset % width).unwrap(),
+            self.min.1 + CoordT::from_usize(offset / width).unwrap(),
+        )
+    }
+
+    // every point with its item, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((CoordT, CoordT), &ItemT)> + '_ {
+        self.vec
+            .iter()
+            .enumerate()
+            .map(|(offset, item)| (self.point_at(offset), item))
+    }
+
+    #[allow(dead_code)]
+    pub fn row(&self, y: CoordT) -> Option<&[ItemT]> {
+        if y < self.min.1 || y > self.max.1 {
+            return None;
+        }
+        let start = self.offset(&(self.min.0, y));
+        Some(&self.vec[start..start + self.width()])
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, x: CoordT) -> Option<impl Iterator<Item = &ItemT> + '_> {
+        if x < self.min.0 || x > self.max.0 {
+            return None;
+        }
+        let start = self.offset(&(x, self.min.1));
+        Some(self.vec[start..].iter().step_by(self.width()))
+    }
+
+    // points from the start (excluding it) in the direction until the edge of the map
+    pub fn ray(
+        &self,
+        start: (CoordT, CoordT),
+        dir: (CoordT, CoordT),
+    ) -> impl Iterator<Item = ((CoordT, CoordT), &ItemT)> + '_ {
+        assert!(
+            dir != (CoordT::zero(), CoordT::zero()),
+            "ray needs a direction"
+        );
+        successors(Some(start), move |pt| Some((pt.0 + dir.0, pt.1 + dir.1)))
+            .skip(1)
+            .map_while(move |pt| self.get(&pt).map(|item| (pt, item)))
+    }
+}
+
+pub fn directions4<CoordT: Signed + Copy>() -> [(CoordT, CoordT); 4] {
+    let (zero, one) = (CoordT::zero(), CoordT::one());
+    [(-one, zero), (one, zero), (zero, -one), (zero, one)]
+}
+
+#[allow(dead_code)]
+pub fn directions8<CoordT: Signed + Copy>() -> [(CoordT, CoordT); 8] {
+    let (zero, one) = (CoordT::zero(), CoordT::one());
+    [
+        (-one, -one),
+        (zero, -one),
+        (one, -one),
+        (-one, zero),
+        (one, zero),
+        (-one, one),
+        (zero, one),
+        (one, one),
+    ]
+}
+
+impl<ItemT: Copy, CoordT: Debug + Copy + Signed + PartialOrd + ToPrimitive + FromPrimitive>
+    Vec2d<ItemT, CoordT>
+{
+    fn neighbours<const N: usize>(
+        &self,
+        pt: (CoordT, CoordT),
+        dirs: [(CoordT, CoordT); N],
+    ) -> impl Iterator<Item = (CoordT, CoordT)> + '_ {
+        dirs.into_iter()
+            .map(move |d| (pt.0 + d.0, pt.1 + d.1))
+            .filter(|pt| self.is_inside(pt))
+    }
+
+    // left, right, up, down; only the ones inside of the map
+    pub fn neighbours4(&self, pt: (CoordT, CoordT)) -> impl Iterator<Item = (CoordT, CoordT)> + '_ {
+        self.neighbours(pt, directions4())
+    }
+
+    // including diagonal ones, row by row
+    #[allow(dead_code)]
+    pub fn neighbours8(&self, pt: (CoordT, CoordT)) -> impl Iterator<Item = (CoordT, CoordT)> + '_ {
+        self.neighbours(pt, directions8())
+    }
+}
+
+// steps of cost 1 to the neighbours inside of the map, all of them; which ones can be
+// taken is up to the day, through SearchSpace::filter_edges
+impl<ItemT, CoordT> SearchSpace for Vec2d<ItemT, CoordT>
+where
+    ItemT: Copy,
+    CoordT: Debug + Copy + Signed + PartialOrd + ToPrimitive + FromPrimitive + Eq + Hash,
+{
+    type Node = (CoordT, CoordT);
+    type Cost = usize;
+
+    fn successors(
+        &self,
+        node: &(CoordT, CoordT),
+    ) -> impl Iterator<Item = ((CoordT, CoordT), usize)> {
+        self.neighbours4(*node).map(|pt| (pt, 1))
+    }
+}
+
+impl<ItemT: Copy, CoordT: Debug + Copy + Num + PartialOrd + ToPrimitive + FromPrimitive>
+    Index<(CoordT, CoordT)> for Vec2d<ItemT, CoordT>
+{
+    type Output = ItemT;
+
+    fn index(&self, pt: (CoordT, CoordT)) -> &ItemT {
+        match self.get(&pt) {
+            Some(item) => item,
+            None => panic!("{:?} is out of map {:?}-{:?}", pt, self.min, self.max),
+        }
+    }
+}
+
+impl<ItemT: Copy, CoordT: Debug + Copy + Num + PartialOrd + ToPrimitive + FromPrimitive>
+    IndexMut<(CoordT, CoordT)> for Vec2d<ItemT, CoordT>
+{
+    fn index_mut(&mut self, pt: (CoordT, CoordT)) -> &mut ItemT {
+        let (min, max) = (self.min, self.max);
+        match self.get_mut(&pt) {
+            Some(item) => item,
+            None => panic!("{:?} is out of map {:?}-{:?}", pt, min, max),
+        }
+    }
+}
+
+impl<ItemT: Copy, CoordT: Debug + Copy + Num + PartialOrd + ToPrimitive + FromPrimitive> Grid
+    for Vec2d<ItemT, CoordT>
+{
+    type Item = ItemT;
+    type Coord = CoordT;
+
+    fn get(&self, pt: &(CoordT, CoordT)) -> Option<&ItemT> {
+        self.get(pt)
+    }
+
+    fn set(&mut self, pt: &(CoordT, CoordT), item: ItemT) -> Result<()> {
+        let (min, max) = (self.min, self.max);
+        let map_pt = self
+            .get_mut(pt)
+            .ok_or_else(|| anyhow!("out of map {:?}, {:?}-{:?}", pt, min, max))?;
+        *map_pt = item;
+        Ok(())
+    }
+
+    fn bounds(&self) -> Option<Bounds<CoordT>> {
+        Some((self.min, self.max))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((CoordT, CoordT), &ItemT)> + '_ {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use itertools::Itertools;
+
+    type Map = Vec2d<u8, i32>;
+
+    fn test_map() -> Map {
+        // 3x2 map with min at (-1, 10)
+        Map::parse("abc\ndef", (-1, 10), |_x, _y, c| -> Result<u8> { Ok(c) }).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let map = test_map();
+        assert_eq!((1, 11), map.max);
+        assert_eq!(Some(&b'a'), map.get(&(-1, 10)));
+        assert_eq!(Some(&b'f'), map.get(&(1, 11)));
+        assert_eq!(None, map.get(&(2, 11)));
+        assert!(Map::parse("ab\nc", (0, 0), |_x, _y, c| -> Result<u8> { Ok(c) }).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut map = test_map();
+        assert_eq!(b'e', map[(0, 11)]);
+        map[(0, 11)] = b'x';
+        assert_eq!(b'x', map[(0, 11)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_map() {
+        let map = test_map();
+        let _ = map[(0, 0)];
+    }
+
+    #[test]
+    fn test_iter() {
+        let map = test_map();
+        assert_eq!(
+            vec![
+                ((-1, 10), b'a'),
+                ((0, 10), b'b'),
+                ((1, 10), b'c'),
+                ((-1, 11), b'd'),
+                ((0, 11), b'e'),
+                ((1, 11), b'f'),
+            ],
+            map.iter().map(|(pt, &c)| (pt, c)).collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_row_column() {
+        let map = test_map();
+        assert_eq!(Some(&b"def"[..]), map.row(11));
+        assert_eq!(None, map.row(12));
+        assert_eq!(
+            Some(vec![b'b', b'e']),
+            map.column(0).map(|it| it.cloned().collect_vec())
+        );
+        assert!(map.column(2).is_none());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let map = test_map();
+        assert_eq!(
+            vec![(-1, 10), (1, 10), (0, 11)],
+            map.neighbours4((0, 10)).collect_vec()
+        );
+        assert_eq!(
+            vec![(-1, 10), (0, 10), (1, 10), (-1, 11), (1, 11)],
+            map.neighbours8((0, 11)).collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_ray() {
+        let map = test_map();
+        assert_eq!(
+            vec![((0, 10), b'b'), ((1, 10), b'c')],
+            map.ray((-1, 10), (1, 0))
+                .map(|(pt, &c)| (pt, c))
+                .collect_vec()
+        );
+        assert_eq!(
+            vec![((0, 11), b'e'), ((-1, 10), b'a')],
+            map.ray((1, 12), (-1, -1))
+                .map(|(pt, &c)| (pt, c))
+                .collect_vec()
+        );
+        assert_eq!(0, map.ray((1, 11), (0, 1)).count());
+    }
+
+    #[test]
+    fn test_display() {
+        let map = test_map();
+        assert_eq!(
+            "abc\ndEf",
+            map.display_with(|pt, &c| if pt == (0, 11) {
+                c.to_ascii_uppercase() as char
+            } else {
+                c as char
+            })
+            .to_string()
+        );
+    }
+}